@@ -1,22 +1,178 @@
+use reqwest::Url;
 use std::path::PathBuf;
 use structopt::StructOpt;
+use validate_lib::{FileEncoding, OutputFormat};
+
+/// Idempotency check parameters
+#[derive(StructOpt, Debug)]
+pub struct IdempotencyArguments {
+    /// Submissions count for every test case, both sequential and concurrent
+    #[structopt(long, default_value = "5")]
+    pub repeats: u32,
+
+    /// Server status which means that the purchase was granted
+    #[structopt(long, default_value = "ok")]
+    pub success_status: String,
+}
+
+/// Differential testing parameters
+#[derive(StructOpt, Debug)]
+pub struct DiffArguments {
+    /// Configs with projects to compare against, projects are matched by name
+    #[structopt(long, required = true, parse(from_os_str))]
+    pub against: Vec<PathBuf>,
+}
+
+/// Offline decode parameters
+#[derive(StructOpt, Debug)]
+pub struct DecodeArguments {
+    /// Base64 payment_info of request or validation_result of server response
+    pub data: String,
+
+    /// Signature to verify with project secret key
+    #[structopt(long)]
+    pub signature: Option<String>,
+
+    /// Project name from configs with secret key, not needed for a single config
+    #[structopt(long)]
+    pub project: Option<String>,
+}
+
+/// Single purchase check parameters
+#[derive(StructOpt, Debug)]
+pub struct CheckArguments {
+    /// Validation server API url
+    #[structopt(long)]
+    pub api_url: Url,
+
+    /// Project name
+    #[structopt(long)]
+    pub project: String,
+
+    /// Environment variable with project secret key
+    #[structopt(long)]
+    pub secret_env: String,
+
+    /// Purchase platform
+    #[structopt(long)]
+    pub platform: String,
+
+    /// Purchased product id
+    #[structopt(long)]
+    pub product_id: String,
+
+    /// Purchase order id
+    #[structopt(long)]
+    pub order_id: String,
+
+    /// File with purchase receipt
+    #[structopt(long, parse(from_os_str))]
+    pub receipt_file: PathBuf,
+
+    /// Receipt file encoding: auto, text or base64
+    #[structopt(long, default_value = "auto")]
+    pub receipt_encoding: FileEncoding,
+
+    /// Purchase receipt signature
+    #[structopt(long)]
+    pub receipt_signature: Option<String>,
+
+    /// Client identifier of purchase
+    #[structopt(long)]
+    pub client_identifier: Option<String>,
+
+    /// Expected purchase status
+    #[structopt(long, default_value = "ok")]
+    pub expect: String,
+}
+
+/// App commands
+#[derive(StructOpt, Debug)]
+#[allow(clippy::large_enum_variant)] // Создается один раз при разборе аргументов
+pub enum AppCommand {
+    /// Submit every test case several times and check that server answers consistently
+    Idempotency(IdempotencyArguments),
+
+    /// Run every test case against two environments and print divergences of answers
+    Diff(DiffArguments),
+
+    /// Decode payment_info or validation_result blob and verify its signature without network
+    Decode(DecodeArguments),
+
+    /// Check single purchase from command line arguments without config
+    Check(CheckArguments),
+
+    /// Interactive terminal UI for browsing and running test cases
+    Tui,
+}
 
 /// App parameters
 #[derive(StructOpt, Debug)]
 #[structopt(name = "basic")]
 pub struct AppArguments {
-    /// Test configs file paths
-    #[structopt(short, long, parse(from_os_str))]
+    /// Test config file path, repeat the option for every config: -c a.yml -c b.yml.
+    /// Can be specified after command too
+    #[structopt(short, long, global = true, number_of_values = 1, parse(from_os_str))]
     pub configs: Vec<PathBuf>,
 
     /// Log level
-    #[structopt(short, parse(from_occurrences))]
-    pub verbose: u8
+    #[structopt(short, global = true, parse(from_occurrences))]
+    pub verbose: u8,
+
+    /// Maximum number of checks executed concurrently across all configs
+    #[structopt(short, long, default_value = "1")]
+    pub jobs: usize,
+
+    /// Stop at the first failed check, remaining checks are not executed
+    #[structopt(long)]
+    pub fail_fast: bool,
+
+    /// Retries count with growing delay for failed checks, passed retry marks check as flaky.
+    /// Scenario steps are never retried
+    #[structopt(long, default_value = "0")]
+    pub retries: u32,
+
+    /// File with failed checks of the last run, written after every functional tests run
+    #[structopt(long, default_value = ".config_test_state.json", parse(from_os_str))]
+    pub state_file: PathBuf,
+
+    /// Execute only checks failed during the last run according to the state file
+    #[structopt(long)]
+    pub rerun_failed: bool,
+
+    /// Output format: text or jsonl with one JSON object per executed check, jsonl is supported only for functional tests
+    #[structopt(long, default_value = "text")]
+    pub output: OutputFormat,
+
+    /// Write JUnit XML report of functional tests to the file
+    #[structopt(long, parse(from_os_str))]
+    pub report_junit: Option<PathBuf>,
+
+    /// Write self-contained HTML report of functional tests to the file
+    #[structopt(long, parse(from_os_str))]
+    pub report_html: Option<PathBuf>,
+
+    /// Print signed requests of all checks without sending anything
+    #[structopt(long)]
+    pub dry_run: bool,
+
+    /// Print dry run requests as ready to paste curl commands
+    #[structopt(long, requires = "dry-run")]
+    pub curl: bool,
+
+    /// Command, functional tests are executed by default
+    #[structopt(subcommand)]
+    pub command: Option<AppCommand>,
 }
 
 impl AppArguments {
     pub fn new() -> Result<Self, eyre::Error> {
-        let args = AppArguments::from_args();
+        // Ошибки разбора возвращаем наверх, чтобы завершиться с кодом ошибки настройки
+        let args = match AppArguments::from_iter_safe(std::env::args_os()) {
+            Ok(args) => args,
+            Err(err) if !err.use_stderr() => err.exit(),
+            Err(err) => return Err(err.into()),
+        };
         args.validate_arguments()?;
         Ok(args)
     }
@@ -31,7 +187,15 @@ impl AppArguments {
             };
         }
 
-        validate_argument!(!self.configs.is_empty(), "Configs array cannot be empty");
+        // Для расшифровки конфиги нужны только при проверке подписи, для разовой проверки не нужны
+        match &self.command {
+            Some(AppCommand::Decode(args)) => validate_argument!(
+                args.signature.is_none() || !self.configs.is_empty(),
+                "Configs are required for signature verify"
+            ),
+            Some(AppCommand::Check(_)) => {}
+            _ => validate_argument!(!self.configs.is_empty(), "Configs array cannot be empty"),
+        }
 
         for config in self.configs.iter() {
             validate_argument!(config.exists(), "Config file does not exist");
@@ -40,6 +204,50 @@ impl AppArguments {
 
         validate_argument!(self.verbose < 3, "Verbose level must be in range [0; 2]");
 
+        validate_argument!(self.jobs > 0, "Jobs count cannot be zero");
+
+        validate_argument!(self.retries <= 10, "Retries count must be in range [0; 10]");
+
+        if self.rerun_failed {
+            validate_argument!(
+                self.state_file.is_file(),
+                "State file does not exist, nothing to rerun"
+            );
+        }
+
+        validate_argument!(
+            !self.dry_run || self.command.is_none(),
+            "Dry run is supported only for functional tests"
+        );
+
+        // Остальные команды выводят текст и таблицы, которые сломали бы поток JSON объектов
+        validate_argument!(
+            self.output == OutputFormat::Text || self.command.is_none(),
+            "Jsonl output is supported only for functional tests"
+        );
+
+        match &self.command {
+            Some(AppCommand::Idempotency(args)) => {
+                validate_argument!(
+                    args.repeats >= 2,
+                    "Idempotency repeats count must be at least 2"
+                );
+            }
+            Some(AppCommand::Diff(args)) => {
+                for config in args.against.iter() {
+                    validate_argument!(config.exists(), "Against config file does not exist");
+                    validate_argument!(config.is_file(), "Against config file is not a file");
+                }
+            }
+            Some(AppCommand::Check(args)) => {
+                validate_argument!(
+                    args.receipt_file.is_file(),
+                    "Receipt file does not exist or is not a file"
+                );
+            }
+            Some(AppCommand::Decode(_)) | Some(AppCommand::Tui) | None => {}
+        }
+
         Ok(())
     }
 }
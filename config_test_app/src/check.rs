@@ -0,0 +1,81 @@
+use crate::app_arguments::CheckArguments;
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+use reqwest::Client;
+use slog::Logger;
+use validate_lib::{
+    check_purchase, read_data_file, CheckError, ProjectInfo, PurchaseData, PurchaseResponse,
+    ResponseData, TestCase,
+};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Выводим расшифрованный ответ сервера целиком
+fn print_response(response: &PurchaseResponse) {
+    let status = &response.purchase_status;
+    println!("HTTP status: {}", response.http_status);
+    println!("Status: {}", status.status);
+    println!("Description: {}", status.description_text());
+    println!("Payload: {}", status.payload_text());
+}
+
+/// Проверяем одну покупку, описанную аргументами командной строки.
+/// Возвращаем false, если проверка не прошла.
+pub async fn execute_check(
+    logger: &Logger,
+    http_client: &Client,
+    args: &CheckArguments,
+) -> Result<bool, eyre::Error> {
+    // Секретный ключ не передаем аргументом, чтобы он не попал в историю команд
+    let secret_key = std::env::var(&args.secret_env).wrap_err_with(|| {
+        format!(
+            "Secret key environment variable {} read failed",
+            args.secret_env
+        )
+    })?;
+    if secret_key.is_empty() {
+        return Err(eyre::eyre!(
+            "Secret key environment variable {} is empty",
+            args.secret_env
+        ));
+    }
+
+    let project = ProjectInfo {
+        api_url: args.api_url.clone(),
+        secret_key,
+        name: args.project.clone(),
+    };
+
+    let test = TestCase {
+        name: None,
+        purchase: PurchaseData {
+            platform: args.platform.clone(),
+            product_id: args.product_id.clone(),
+            order_id: args.order_id.clone(),
+            receipt: read_data_file(&args.receipt_file, args.receipt_encoding)
+                .wrap_err("Receipt file read failed")?,
+            receipt_signature: args.receipt_signature.clone(),
+            client_identifier: args.client_identifier.clone(),
+        },
+        response: ResponseData {
+            status: args.expect.clone(),
+        },
+        weight: 1.0,
+    };
+
+    match check_purchase(logger, http_client, &project, &test).await {
+        Ok(response) => {
+            print_response(&response);
+            println!("{}", "Check passed".green());
+            Ok(true)
+        }
+        Err(err) => {
+            // При несовпадении статуса ответ сервера тоже интересен
+            if let Some(CheckError::StatusMismatch { response, .. }) = CheckError::find(&err) {
+                print_response(response);
+            }
+            eprintln!("{}: {err:#}", "Check failed".red());
+            Ok(false)
+        }
+    }
+}
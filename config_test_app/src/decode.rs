@@ -0,0 +1,65 @@
+use crate::app_arguments::DecodeArguments;
+use eyre::{ContextCompat, WrapErr};
+use owo_colors::OwoColorize;
+use validate_lib::{calc_signature, Config};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Расшифровываем payment_info или validation_result и проверяем подпись.
+/// Возвращаем false, если подпись не совпала.
+pub fn execute_decode(configs: &[Config], args: &DecodeArguments) -> Result<bool, eyre::Error> {
+    let data = args.data.trim();
+
+    // Данные закодированы в base64, внутри JSON
+    let json_data = base64::decode(data).wrap_err("Data base64 decode failed")?;
+    let json_string = std::str::from_utf8(&json_data).wrap_err("UTF-8 parsing failed")?;
+    match serde_json::from_str::<serde_json::Value>(json_string) {
+        Ok(value) => {
+            let pretty = serde_json::to_string_pretty(&value).wrap_err("Json format failed")?;
+            println!("{pretty}");
+        }
+        // Покажем как есть, вдруг там что-то полезное
+        Err(err) => {
+            eprintln!("{}: {err}", "Decoded data is not a valid json".yellow());
+            println!("{json_string}");
+        }
+    }
+
+    let signature = match &args.signature {
+        Some(signature) => signature.trim(),
+        None => return Ok(true),
+    };
+
+    // Ключ берем из проекта с указанным именем или из единственного конфига
+    let project = match &args.project {
+        Some(name) => configs
+            .iter()
+            .map(|config| &config.project)
+            .find(|project| &project.name == name)
+            .wrap_err_with(|| format!("No project {name} in configs"))?,
+        None => match configs {
+            [config] => &config.project,
+            _ => return Err(eyre::eyre!("Project name is required to select secret key")),
+        },
+    };
+
+    let calculated = calc_signature(data.as_bytes(), project.secret_key.as_bytes())
+        .wrap_err("Signature calculate failed")?;
+    if calculated == signature {
+        println!(
+            "{}: project {}",
+            "Signature valid".green(),
+            project.name.blue()
+        );
+        Ok(true)
+    } else {
+        println!(
+            "{}: project {}, calculated {} != received {}",
+            "Signature mismatch".red(),
+            project.name.blue(),
+            calculated,
+            signature
+        );
+        Ok(false)
+    }
+}
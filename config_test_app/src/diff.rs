@@ -0,0 +1,185 @@
+use owo_colors::OwoColorize;
+use reqwest::Client;
+use slog::{trace, Logger};
+use validate_lib::{
+    request_purchase, CheckError, Config, ErrorKind, ProjectInfo, PurchaseResponse,
+};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Сравниваемые поля ответа
+const DIFF_FIELDS: [&str; 5] = ["http_status", "status", "description", "payload", "error"];
+
+/// Ответ одного из окружений в виде значений сравниваемых полей
+fn outcome_values(result: &Result<PurchaseResponse, eyre::Error>) -> [String; 5] {
+    const EMPTY: &str = "-";
+
+    match result {
+        Ok(response) => {
+            let purchase_status = &response.purchase_status;
+            [
+                response.http_status.as_u16().to_string(),
+                purchase_status.status.clone(),
+                purchase_status.description_text().to_owned(),
+                purchase_status.payload_text(),
+                EMPTY.to_owned(),
+            ]
+        }
+        Err(err) => {
+            // Для ошибок HTTP сравниваем статус и текст ответа,
+            // для остальных только верхний уровень ошибки, так как ниже там адреса серверов
+            let (http_status, error) = match CheckError::find(err) {
+                Some(CheckError::HttpStatus { status, text }) => (
+                    status.as_u16().to_string(),
+                    text.clone().unwrap_or_else(|| EMPTY.to_owned()),
+                ),
+                _ => (EMPTY.to_owned(), err.to_string()),
+            };
+            [
+                http_status,
+                EMPTY.to_owned(),
+                EMPTY.to_owned(),
+                EMPTY.to_owned(),
+                error,
+            ]
+        }
+    }
+}
+
+/// Полный текст ошибки, если запрос не дошел до сервера или не дождался ответа.
+/// Одинаковые такие ошибки на обоих окружениях не означают одинаковых ответов.
+fn transport_error(result: &Result<PurchaseResponse, eyre::Error>) -> Option<String> {
+    match result {
+        Err(err)
+            if matches!(
+                ErrorKind::of(err),
+                ErrorKind::Transport | ErrorKind::Timeout
+            ) =>
+        {
+            Some(format!("{err:#}"))
+        }
+        _ => None,
+    }
+}
+
+/// Выводим таблицу с выравниванием колонок
+fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+    for row in rows.iter() {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .into_iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join(" | ")
+    };
+
+    println!("{}", format_row(headers.to_vec()).bold());
+    for row in rows.iter() {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
+
+/// Прогоняем каждый тест конфига на двух окружениях и выводим таблицу расхождений ответов.
+/// Возвращаем количество тестов с расхождениями или без ответа одного из окружений.
+pub async fn execute_diff(
+    logger: &Logger,
+    http_client: &Client,
+    config: &Config,
+    other_project: &ProjectInfo,
+) -> usize {
+    let Config { project, tests, .. } = config;
+
+    println!(
+        "Compare {} with {}",
+        project.api_url.blue(),
+        other_project.api_url.blue()
+    );
+
+    let mut rows = Vec::new();
+    let mut divergent_tests = 0;
+    let mut failed_tests = 0;
+    for (i, test) in tests.iter().enumerate() {
+        let index = i + 1;
+
+        // Создаем логирование для данной задачи с контекстом
+        let logger = logger.new(
+            slog::o!("index" => format!("{}", index), "product" => test.purchase.product_id.clone()),
+        );
+
+        trace!(logger, "Diff start");
+
+        // Запросы к обоим окружениям выполняем одновременно
+        let (left, right) = futures::join!(
+            request_purchase(&logger, http_client, project, &test.purchase),
+            request_purchase(&logger, http_client, other_project, &test.purchase)
+        );
+
+        // Без ответа одного из окружений сравнивать нечего, это отдельная проблема
+        let (left_error, right_error) = (transport_error(&left), transport_error(&right));
+        if left_error.is_some() || right_error.is_some() {
+            failed_tests += 1;
+            rows.push(vec![
+                index.to_string(),
+                test.purchase.order_id.clone(),
+                test.purchase.platform.clone(),
+                "transport".to_owned(),
+                left_error.unwrap_or_else(|| "-".to_owned()),
+                right_error.unwrap_or_else(|| "-".to_owned()),
+            ]);
+            continue;
+        }
+
+        let left = outcome_values(&left);
+        let right = outcome_values(&right);
+
+        let mut divergent = false;
+        for ((field, left), right) in DIFF_FIELDS.iter().zip(left).zip(right) {
+            if left != right {
+                divergent = true;
+                rows.push(vec![
+                    index.to_string(),
+                    test.purchase.order_id.clone(),
+                    test.purchase.platform.clone(),
+                    field.to_string(),
+                    left,
+                    right,
+                ]);
+            }
+        }
+        if divergent {
+            divergent_tests += 1;
+        }
+    }
+
+    if rows.is_empty() {
+        println!(
+            "{}: all {} tests answered identically",
+            "No divergences".green(),
+            tests.len()
+        );
+    } else {
+        println!(
+            "{}: {} of {} tests diverged, {} failed to get answers",
+            "Problems found".red(),
+            divergent_tests,
+            tests.len(),
+            failed_tests
+        );
+        print_table(
+            &["Test", "Order id", "Platform", "Field", "Left", "Right"],
+            &rows,
+        );
+    }
+
+    divergent_tests + failed_tests
+}
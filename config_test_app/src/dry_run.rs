@@ -0,0 +1,72 @@
+use eyre::WrapErr;
+use slog::Logger;
+use validate_lib::{build_request_body, Config, ProjectInfo, TestCase};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Экранируем строку для вставки в shell в одинарных кавычках
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r#"'\''"#))
+}
+
+/// Выводим запрос к серверу для одной проверки
+fn print_request(
+    logger: &Logger,
+    project: &ProjectInfo,
+    test: &TestCase,
+    title: &str,
+    curl: bool,
+) -> Result<(), eyre::Error> {
+    let body = build_request_body(logger, project, &test.purchase)
+        .wrap_err_with(|| format!("Request build failed for {title}"))?;
+
+    println!(
+        "# {title}, project {}, expected status {}",
+        project.name, test.response.status
+    );
+    if curl {
+        let body_json = serde_json::to_string(&body).wrap_err("Request serialize failed")?;
+        println!(
+            "curl -X POST {} -H 'Content-Type: application/json' -d {}",
+            shell_quote(project.api_url.as_str()),
+            shell_quote(&body_json)
+        );
+    } else {
+        let body_json = serde_json::to_string_pretty(&body).wrap_err("Request serialize failed")?;
+        println!("POST {}", project.api_url);
+        println!("{body_json}");
+    }
+    println!();
+
+    Ok(())
+}
+
+/// Выводим подписанные запросы всех тестов и шагов сценариев конфига, ничего не отправляя
+pub fn print_dry_run(logger: &Logger, config: &Config, curl: bool) -> Result<(), eyre::Error> {
+    for (i, test) in config.tests.iter().enumerate() {
+        let mut title = format!("test number {}", i + 1);
+        if let Some(name) = &test.name {
+            title.push_str(&format!(r#", name "{name}""#));
+        }
+        print_request(logger, &config.project, test, &title, curl)?;
+    }
+
+    for scenario in config.scenarios.iter() {
+        for step in scenario.steps.iter() {
+            let title = format!(
+                r#"scenario "{}", step "{}""#,
+                scenario.name,
+                step.test.name.as_deref().unwrap_or_default()
+            );
+            print_request(
+                logger,
+                step.project(&config.project),
+                &step.test,
+                &title,
+                curl,
+            )?;
+        }
+    }
+
+    Ok(())
+}
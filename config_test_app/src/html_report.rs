@@ -0,0 +1,210 @@
+use crate::{
+    junit::escape_xml,
+    results::{request_summary, CaseOutcome, CaseResult, OutcomeCounts, ProjectResult},
+};
+use eyre::WrapErr;
+use std::{collections::BTreeMap, fmt::Write, path::Path, time::Duration};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; }
+th { background: #f0f0f0; }
+details { margin: 2px 0; padding: 4px 8px; border-left: 4px solid #ccc; }
+details.passed { border-color: #2a2; }
+details.failed { border-color: #d22; }
+details.skipped { border-color: #cc2; }
+summary { cursor: pointer; }
+pre { background: #f7f7f7; padding: 8px; white-space: pre-wrap; word-break: break-all; }
+.passed { color: #2a2; }
+.failed { color: #d22; }
+.skipped { color: #aa0; }
+"#;
+
+/// Ревизия git текущей директории, если получится ее узнать
+fn git_revision() -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let revision = String::from_utf8(output.stdout).ok()?;
+    Some(revision.trim().to_owned())
+}
+
+/// Ячейки таблицы с количеством проверок по итогам
+fn counts_cells(counts: &OutcomeCounts) -> String {
+    format!(
+        r#"<td class="passed">{}</td><td class="failed">{}</td><td class="skipped">{}</td>"#,
+        counts.passed, counts.failed, counts.skipped
+    )
+}
+
+/// Раскрываемый блок с подробностями проверки
+fn write_case(html: &mut String, case: &CaseResult) -> std::fmt::Result {
+    let (class, verdict) = match case.outcome {
+        CaseOutcome::Passed(_) if case.is_flaky() => ("passed", "Flaky"),
+        CaseOutcome::Passed(_) => ("passed", "Passed"),
+        CaseOutcome::Failed(_) => ("failed", "Failed"),
+        CaseOutcome::Skipped => ("skipped", "Skipped"),
+    };
+    let purchase = &case.test.purchase;
+
+    writeln!(html, r#"<details class="{class}">"#)?;
+    writeln!(
+        html,
+        r#"<summary><span class="{class}">{verdict}</span>: {}, order_id "{}", platform "{}" ({:.3}s)</summary>"#,
+        escape_xml(&case.title()),
+        escape_xml(&purchase.order_id),
+        escape_xml(&purchase.platform),
+        case.duration.as_secs_f64()
+    )?;
+
+    // Краткое описание запроса, сам чек слишком большой для отчета
+    writeln!(html, "<table>")?;
+    let mut request_rows = request_summary(case.project, case.test);
+    request_rows.push(("Attempts", case.attempts.to_string()));
+    for (name, value) in request_rows.iter() {
+        writeln!(
+            html,
+            "<tr><th>{}</th><td>{}</td></tr>",
+            name,
+            escape_xml(value)
+        )?;
+    }
+    writeln!(html, "</table>")?;
+
+    if let Some(purchase_status) = case.purchase_status() {
+        writeln!(html, "<p>Server response:</p>")?;
+        writeln!(
+            html,
+            "<pre>status: {}\ndescription: {}\npayload: {}</pre>",
+            escape_xml(&purchase_status.status),
+            escape_xml(purchase_status.description_text()),
+            escape_xml(&purchase_status.payload_text())
+        )?;
+    }
+
+    if let CaseOutcome::Failed(err) = &case.outcome {
+        writeln!(html, "<p>Error chain:</p>")?;
+        writeln!(html, "<pre>")?;
+        for (i, err) in err.chain().enumerate() {
+            writeln!(html, "{}: {}", i, escape_xml(&err.to_string()))?;
+        }
+        writeln!(html, "</pre>")?;
+    }
+
+    writeln!(html, "</details>")
+}
+
+/// Пишем отчет в виде одного статического HTML файлика
+pub fn write_html_report(
+    path: &Path,
+    results: &[ProjectResult],
+    total_duration: Duration,
+) -> Result<(), eyre::Error> {
+    let mut html = String::new();
+
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(html, r#"<html><head><meta charset="utf-8">"#)?;
+    writeln!(html, "<title>Purchase validation report</title>")?;
+    writeln!(html, "<style>{STYLE}</style>")?;
+    writeln!(html, "</head><body>")?;
+    writeln!(html, "<h1>Purchase validation report</h1>")?;
+
+    // Информация о запуске
+    writeln!(html, "<table>")?;
+    writeln!(
+        html,
+        "<tr><th>Time</th><td>{}</td></tr>",
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S %z")
+    )?;
+    writeln!(
+        html,
+        "<tr><th>Duration</th><td>{:.2}s</td></tr>",
+        total_duration.as_secs_f64()
+    )?;
+    if let Some(revision) = git_revision() {
+        writeln!(
+            html,
+            "<tr><th>Git revision</th><td>{}</td></tr>",
+            escape_xml(&revision)
+        )?;
+    }
+    writeln!(html, "</table>")?;
+
+    // Сводка по проектам
+    writeln!(html, "<h2>Summary</h2>")?;
+    writeln!(html, "<table>")?;
+    writeln!(
+        html,
+        "<tr><th>Project</th><th>Config</th><th>API host</th><th>Passed</th><th>Failed</th><th>Skipped</th><th>Duration</th></tr>"
+    )?;
+    for result in results.iter() {
+        let project = &result.config.project;
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td>{}<td>{:.2}s</td></tr>",
+            escape_xml(&project.name),
+            escape_xml(&result.config_path.display().to_string()),
+            escape_xml(project.api_url.host_str().unwrap_or_default()),
+            counts_cells(&OutcomeCounts::from_cases(result.cases.iter())),
+            result.duration.as_secs_f64()
+        )?;
+    }
+    let all_counts =
+        OutcomeCounts::from_cases(results.iter().flat_map(|result| result.cases.iter()));
+    writeln!(
+        html,
+        "<tr><th colspan=\"3\">Total</th>{}<td>{:.2}s</td></tr>",
+        counts_cells(&all_counts),
+        total_duration.as_secs_f64()
+    )?;
+    writeln!(html, "</table>")?;
+
+    // Подробности по каждому проекту
+    for result in results.iter() {
+        writeln!(
+            html,
+            "<h2>{} <small>({})</small></h2>",
+            escape_xml(&result.config.project.name),
+            escape_xml(&result.config_path.display().to_string())
+        )?;
+
+        // Разбивка по платформам
+        let mut platforms: BTreeMap<&str, Vec<&CaseResult>> = BTreeMap::new();
+        for case in result.cases.iter() {
+            platforms
+                .entry(case.test.purchase.platform.as_str())
+                .or_default()
+                .push(case);
+        }
+        writeln!(html, "<table>")?;
+        writeln!(
+            html,
+            "<tr><th>Platform</th><th>Passed</th><th>Failed</th><th>Skipped</th></tr>"
+        )?;
+        for (platform, cases) in platforms.iter() {
+            writeln!(
+                html,
+                "<tr><td>{}</td>{}</tr>",
+                escape_xml(platform),
+                counts_cells(&OutcomeCounts::from_cases(cases.iter().copied()))
+            )?;
+        }
+        writeln!(html, "</table>")?;
+
+        for case in result.cases.iter() {
+            write_case(&mut html, case)?;
+        }
+    }
+
+    writeln!(html, "</body></html>")?;
+
+    std::fs::write(path, html)
+        .wrap_err_with(|| format!("Report file {} write failed", path.display()))
+}
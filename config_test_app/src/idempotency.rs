@@ -0,0 +1,118 @@
+use crate::app_arguments::IdempotencyArguments;
+use owo_colors::OwoColorize;
+use reqwest::Client;
+use slog::{trace, Logger};
+use std::collections::BTreeMap;
+use validate_lib::{request_purchase, Config, PurchaseResponse};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Ответ сервера в виде, пригодном для сравнения
+fn answer_text(result: &Result<PurchaseResponse, eyre::Error>) -> String {
+    match result {
+        Ok(response) => response.purchase_status.status.clone(),
+        Err(err) => format!("error: {err:#}"),
+    }
+}
+
+/// Сводка по ответам в виде "ok x1, already_used x4"
+fn answers_summary(answers: &[String]) -> String {
+    let mut counts = BTreeMap::<&str, usize>::new();
+    for answer in answers.iter() {
+        *counts.entry(answer).or_default() += 1;
+    }
+    counts
+        .into_iter()
+        .map(|(answer, count)| format!("{answer} x{count}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Отправляем каждый тест много раз одновременно и последовательно, проверяем согласованность ответов.
+/// Одновременные запросы идут первыми, пока покупка еще не была использована.
+/// Возвращаем количество тестов с проблемами.
+pub async fn execute_idempotency_check(
+    logger: &Logger,
+    http_client: &Client,
+    config: &Config,
+    args: &IdempotencyArguments,
+) -> usize {
+    let Config { project, tests, .. } = config;
+
+    let mut problems_count = 0;
+    for (i, test) in tests.iter().enumerate() {
+        let index = i + 1;
+
+        // Создаем логирование для данной задачи с контекстом
+        let logger = logger.new(
+            slog::o!("index" => format!("{}", index), "product" => test.purchase.product_id.clone()),
+        );
+
+        trace!(logger, "Idempotency check start");
+
+        // Одновременная отправка одной и той же покупки
+        let concurrent_results = futures::future::join_all(
+            (0..args.repeats)
+                .map(|_| request_purchase(&logger, http_client, project, &test.purchase)),
+        )
+        .await;
+
+        // Последовательная отправка
+        let mut sequential_results = Vec::with_capacity(args.repeats as usize);
+        for _ in 0..args.repeats {
+            sequential_results
+                .push(request_purchase(&logger, http_client, project, &test.purchase).await);
+        }
+
+        // Без ответа сервера об идемпотентности судить нельзя, даже если ошибки одинаковые
+        let errors_count = concurrent_results
+            .iter()
+            .chain(sequential_results.iter())
+            .filter(|result| result.is_err())
+            .count();
+
+        let concurrent_answers: Vec<String> = concurrent_results.iter().map(answer_text).collect();
+        let sequential_answers: Vec<String> = sequential_results.iter().map(answer_text).collect();
+
+        // Покупка может быть засчитана не более одного раза за все отправки
+        let granted_count = concurrent_answers
+            .iter()
+            .chain(sequential_answers.iter())
+            .filter(|answer| **answer == args.success_status)
+            .count();
+
+        // Повторные последовательные отправки должны получать один и тот же ответ,
+        // одновременные тоже, кроме одной засчитанной
+        let concurrent_rest: Vec<&String> = concurrent_answers
+            .iter()
+            .filter(|answer| **answer != args.success_status)
+            .collect();
+        let consistent = sequential_answers.windows(2).all(|pair| pair[0] == pair[1])
+            && concurrent_rest.windows(2).all(|pair| pair[0] == pair[1]);
+
+        let verdict = if granted_count > 1 {
+            problems_count += 1;
+            "Double grant".red().to_string()
+        } else if errors_count > 0 {
+            problems_count += 1;
+            "Request errors".red().to_string()
+        } else if !consistent {
+            problems_count += 1;
+            "Inconsistent".red().to_string()
+        } else {
+            "Idempotent".green().to_string()
+        };
+
+        println!(
+            r#"{}: test number "{}", order_id: "{}", platform "{}", concurrent [{}], sequential [{}]"#,
+            verdict,
+            index,
+            test.purchase.order_id,
+            test.purchase.platform,
+            answers_summary(&concurrent_answers),
+            answers_summary(&sequential_answers)
+        );
+    }
+
+    problems_count
+}
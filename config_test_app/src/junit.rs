@@ -0,0 +1,131 @@
+use crate::results::{CaseOutcome, CaseResult, OutcomeCounts, ProjectResult};
+use eyre::WrapErr;
+use std::{fmt::Write, path::Path};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Экранируем текст для XML, подходит и для HTML
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Управляющие символы в XML 1.0 недопустимы
+            c if c.is_control() && c != '\n' && c != '\r' && c != '\t' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Имя теста в отчете: номер, order_id и платформа
+fn case_name(case: &CaseResult) -> String {
+    let purchase = &case.test.purchase;
+    let mut name = format!(
+        "#{} {} {}",
+        case.index, purchase.order_id, purchase.platform
+    );
+    if let Some(case_name) = &case.test.name {
+        name.push_str(&format!(" ({case_name})"));
+    }
+    name
+}
+
+/// Пишем отчет в формате JUnit XML: testsuite на каждый конфиг, testcase на каждую проверку
+pub fn write_junit_report(path: &Path, results: &[ProjectResult]) -> Result<(), eyre::Error> {
+    let mut xml = String::new();
+
+    let all_cases = results.iter().flat_map(|result| result.cases.iter());
+    let counts = OutcomeCounts::from_cases(all_cases);
+    let total_time: f64 = results
+        .iter()
+        .map(|result| result.duration.as_secs_f64())
+        .sum();
+
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        xml,
+        r#"<testsuites name="config_test_app" tests="{}" failures="{}" errors="0" skipped="{}" time="{:.3}">"#,
+        counts.total(),
+        counts.failed,
+        counts.skipped,
+        total_time
+    )?;
+
+    for result in results.iter() {
+        let counts = OutcomeCounts::from_cases(result.cases.iter());
+        writeln!(
+            xml,
+            r#"  <testsuite name="{}" file="{}" tests="{}" failures="{}" errors="0" skipped="{}" time="{:.3}">"#,
+            escape_xml(&result.config.project.name),
+            escape_xml(&result.config_path.display().to_string()),
+            counts.total(),
+            counts.failed,
+            counts.skipped,
+            result.duration.as_secs_f64()
+        )?;
+
+        for case in result.cases.iter() {
+            // Шаги сценариев группируем по сценарию
+            let class_name = match case.scenario {
+                Some(scenario) => format!("{}.{}", case.project.name, scenario),
+                None => case.project.name.clone(),
+            };
+
+            write!(
+                xml,
+                r#"    <testcase name="{}" classname="{}" time="{:.3}""#,
+                escape_xml(&case_name(case)),
+                escape_xml(&class_name),
+                case.duration.as_secs_f64()
+            )?;
+
+            match &case.outcome {
+                CaseOutcome::Passed(_) if case.is_flaky() => {
+                    writeln!(xml, ">")?;
+                    writeln!(
+                        xml,
+                        "      <system-out>Flaky: passed after {} attempts</system-out>",
+                        case.attempts
+                    )?;
+                    writeln!(xml, "    </testcase>")?;
+                }
+                CaseOutcome::Passed(_) => {
+                    writeln!(xml, "/>")?;
+                }
+                CaseOutcome::Skipped => {
+                    writeln!(xml, ">")?;
+                    writeln!(xml, "      <skipped/>")?;
+                    writeln!(xml, "    </testcase>")?;
+                }
+                CaseOutcome::Failed(err) => {
+                    // В сообщении вся цепочка одной строкой, в теле по строке на каждую ошибку
+                    let chain = err
+                        .chain()
+                        .map(|err| err.to_string())
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    writeln!(xml, ">")?;
+                    writeln!(
+                        xml,
+                        r#"      <failure message="{}">{}</failure>"#,
+                        escape_xml(&format!("{err:#}")),
+                        escape_xml(&chain)
+                    )?;
+                    writeln!(xml, "    </testcase>")?;
+                }
+            }
+        }
+
+        writeln!(xml, "  </testsuite>")?;
+    }
+
+    writeln!(xml, "</testsuites>")?;
+
+    std::fs::write(path, xml)
+        .wrap_err_with(|| format!("Report file {} write failed", path.display()))
+}
@@ -1,16 +1,50 @@
 mod app_arguments;
+mod check;
+mod decode;
+mod diff;
+mod dry_run;
+mod html_report;
+mod idempotency;
+mod junit;
+mod printer;
+mod results;
+mod runner;
+mod state;
+mod tui;
 
-use crate::app_arguments::AppArguments;
-use eyre::WrapErr;
+use crate::{
+    app_arguments::{AppArguments, AppCommand},
+    check::execute_check,
+    decode::execute_decode,
+    diff::execute_diff,
+    dry_run::print_dry_run,
+    html_report::write_html_report,
+    idempotency::execute_idempotency_check,
+    junit::write_junit_report,
+    printer::Printer,
+    results::OutcomeCounts,
+    runner::{run_functional_tests, RunOptions},
+    state::{canonical_config_path, RunState},
+    tui::execute_tui,
+};
+use eyre::{ContextCompat, WrapErr};
 use owo_colors::OwoColorize;
-use reqwest::Client;
-use slog::{debug, trace, Drain, Level, Logger};
+use slog::{debug, warn, Drain, Level, Logger};
 use slog_async::OverflowStrategy;
-use validate_lib::{check_purchase, Config};
+use std::{path::PathBuf, process::ExitCode, time::Instant};
+use validate_lib::{Config, OutputFormat};
 // use std::sync::{Arc};
 
 //////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
+/// Код завершения, если какие-то тесты не прошли
+const EXIT_CODE_TESTS_FAILED: u8 = 1;
+
+/// Код завершения при ошибках конфигов, аргументов или настройки транспорта
+const EXIT_CODE_SETUP_FAILED: u8 = 2;
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
 fn init_logs(app_arguments: &AppArguments) -> Logger {
     /*const LOG_VAR: &str = "RUST_LOG";
     if env::var(LOG_VAR).is_err() {
@@ -18,6 +52,11 @@ fn init_logs(app_arguments: &AppArguments) -> Logger {
     }
     pretty_env_logger::try_init().expect("Logger setup failed");*/
 
+    // Вывод логов сломает интерфейс терминала
+    if let Some(AppCommand::Tui) = &app_arguments.command {
+        return slog::Logger::root(slog::Discard, slog::o!());
+    }
+
     let level = match app_arguments.verbose {
         0 => Level::Info,
         1 => Level::Debug,
@@ -25,7 +64,11 @@ fn init_logs(app_arguments: &AppArguments) -> Logger {
         _ => panic!("Invalid verbose level"),
     };
 
-    let term_decor = slog_term::TermDecorator::new().stdout().build();
+    // В режиме JSON Lines stdout занят записями о проверках
+    let term_decor = match app_arguments.output {
+        OutputFormat::Text => slog_term::TermDecorator::new().stdout().build(),
+        OutputFormat::Jsonl => slog_term::TermDecorator::new().stderr().build(),
+    };
     let term_drain = slog_term::FullFormat::new(term_decor)
         .use_file_location()
         .build()
@@ -52,35 +95,9 @@ fn init_logs(app_arguments: &AppArguments) -> Logger {
     // let _guard = slog_stdlog::init().expect("Slog as log backend");
 }
 
-/// Выполняем обработку тестовых платежей
-async fn execute_tests(logger: &Logger, http_client: &Client, config: &Config) {
-    // Разворачиваем на отдельные поля
-    let Config { project, tests } = config;
-
-    for (i, test) in tests.iter().enumerate() {
-        let index = i+1;
-
-        // Создаем логирование для данной задачи с контекстом
-        let logger = logger.new(
-            slog::o!("index" => format!("{}", index), "product" => test.purchase.product_id.clone()),
-        );
-
-        trace!(logger, "Test start");
-
-        match check_purchase(&logger, http_client, project, test).await {
-            Ok(_) => {
-                println!(r#"{}: test number "{}", order_id: "{}", platform "{}""#, "Test passed".green(), index, test.purchase.order_id, test.purchase.platform);
-            }
-            Err(err) => {
-                eprintln!(r#"{}: test number "{}", order_id: "{}", platform "{}", err: "{err:#}""#, "Test failed".red(), index, test.purchase.order_id, test.purchase.platform);
-                // std::process::exit(1);
-            }
-        }
-    }
-}
-
-#[tokio::main]
-async fn main() -> Result<(), eyre::Error> {
+/// Выполняем работу приложения, возвращаем true, если все проверки прошли успешно.
+/// Ошибка означает проблемы с аргументами, конфигами или настройкой транспорта.
+async fn run() -> Result<bool, eyre::Error> {
     // Бектрейсы в ошибках
     color_eyre::install().wrap_err("Color eyre initialize failed")?;
 
@@ -107,16 +124,170 @@ async fn main() -> Result<(), eyre::Error> {
     };
     debug!(logger, "App configs: {:?}", configs);
 
+    // Проекты, с которыми сравниваем в режиме сравнения окружений
+    let against_configs = match &app_arguments.command {
+        Some(AppCommand::Diff(args)) => {
+            let mut configs = Vec::new();
+            for path in args.against.iter() {
+                let config =
+                    Config::parse_from_file(path).wrap_err("Against config load failed")?;
+                configs.push(config);
+            }
+            configs
+        }
+        _ => Vec::new(),
+    };
+
+    // Расшифровка выполняется без обращения к серверу
+    if let Some(AppCommand::Decode(args)) = &app_arguments.command {
+        return execute_decode(&configs, args).wrap_err("Decode failed");
+    }
+
+    // Только выводим запросы, ничего не отправляя
+    if app_arguments.dry_run {
+        for config in configs.iter() {
+            print_dry_run(&logger, config, app_arguments.curl).wrap_err("Dry run failed")?;
+        }
+        return Ok(true);
+    }
+
     // Создаем переиспользуемый HTTP клиент
     let http_client = reqwest::ClientBuilder::new()
         .build()
         .wrap_err("HTTP clien build failed")?;
 
-    // Идем по списку конфигов и прогоняем каждый
-    for config in configs.iter() {
-        println!("Begin project: {}", config.project.name.blue());
-        execute_tests(&logger, &http_client, config).await;
+    // Разовая проверка покупки без конфига
+    if let Some(AppCommand::Check(args)) = &app_arguments.command {
+        return execute_check(&logger, &http_client, args).await;
+    }
+
+    // Интерактивный режим
+    if let Some(AppCommand::Tui) = &app_arguments.command {
+        return execute_tui(
+            &logger,
+            &http_client,
+            &configs,
+            app_arguments.jobs,
+            app_arguments.retries,
+        )
+        .await;
+    }
+
+    let printer = Printer::new(app_arguments.output);
+    let begin_time = Instant::now();
+
+    let mut problems_count = 0;
+    match &app_arguments.command {
+        None => {
+            // Упавшие при прошлом запуске проверки, нужны и для перезапуска,
+            // и чтобы не потерять при сохранении те, что в этот раз не выполнятся
+            let previous_state = if app_arguments.rerun_failed {
+                let state = RunState::load(&app_arguments.state_file)
+                    .wrap_err("Rerun state load failed")?;
+                let config_paths: Vec<PathBuf> = app_arguments
+                    .configs
+                    .iter()
+                    .map(|path| canonical_config_path(path))
+                    .collect();
+                if !state.has_failures_for(&config_paths) {
+                    warn!(
+                        logger,
+                        "None of {} failed checks in state file {} belong to given configs, nothing to rerun",
+                        state.failed.len(),
+                        app_arguments.state_file.display()
+                    );
+                }
+                Some(state)
+            } else if app_arguments.state_file.is_file() {
+                match RunState::load(&app_arguments.state_file) {
+                    Ok(state) => Some(state),
+                    Err(err) => {
+                        warn!(logger, "Previous run state is ignored: {:#}", err);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            let rerun_state = previous_state
+                .as_ref()
+                .filter(|_| app_arguments.rerun_failed);
+
+            // Прогоняем тесты всех конфигов
+            let options = RunOptions {
+                jobs: app_arguments.jobs,
+                fail_fast: app_arguments.fail_fast,
+                retries: app_arguments.retries,
+                rerun_state,
+            };
+            let results = run_functional_tests(
+                &logger,
+                &http_client,
+                &printer,
+                &configs,
+                &app_arguments.configs,
+                &options,
+            )
+            .await;
+
+            // Сохраняем упавшие проверки для перезапуска
+            RunState::from_results(&results, previous_state.as_ref())
+                .save(&app_arguments.state_file)
+                .wrap_err("Run state save failed")?;
+
+            // Итоговая сводка по функциональным тестам
+            let total_duration = begin_time.elapsed();
+            printer.summary(&results, total_duration);
+            let counts =
+                OutcomeCounts::from_cases(results.iter().flat_map(|result| result.cases.iter()));
+            problems_count += counts.failed;
+
+            if let Some(path) = &app_arguments.report_junit {
+                write_junit_report(path, &results).wrap_err("JUnit report write failed")?;
+            }
+            if let Some(path) = &app_arguments.report_html {
+                write_html_report(path, &results, total_duration)
+                    .wrap_err("HTML report write failed")?;
+            }
+        }
+        Some(AppCommand::Idempotency(args)) => {
+            for config in configs.iter() {
+                printer.project_begin(&config.project);
+                let count = execute_idempotency_check(&logger, &http_client, config, args).await;
+                if count > 0 {
+                    eprintln!("Idempotency problems found: {}", count.red());
+                }
+                problems_count += count;
+            }
+        }
+        Some(AppCommand::Diff(_)) => {
+            for config in configs.iter() {
+                printer.project_begin(&config.project);
+                let other_project = against_configs
+                    .iter()
+                    .map(|against| &against.project)
+                    .find(|project| project.name == config.project.name)
+                    .wrap_err_with(|| {
+                        format!("No project {} in against configs", config.project.name)
+                    })?;
+                problems_count += execute_diff(&logger, &http_client, config, other_project).await;
+            }
+        }
+        // Эти команды уже выполнены выше
+        Some(AppCommand::Decode(_)) | Some(AppCommand::Check(_)) | Some(AppCommand::Tui) => {}
     }
 
-    Ok(())
+    Ok(problems_count == 0)
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(EXIT_CODE_TESTS_FAILED),
+        Err(err) => {
+            eprintln!("Error: {err:?}");
+            ExitCode::from(EXIT_CODE_SETUP_FAILED)
+        }
+    }
 }
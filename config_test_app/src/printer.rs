@@ -0,0 +1,153 @@
+use crate::results::{print_summary, CaseOutcome, CaseResult, ProjectResult};
+use owo_colors::OwoColorize;
+use std::time::Duration;
+use validate_lib::{OutputFormat, ProjectInfo, Scenario};
+
+/// Вывод хода выполнения функциональных тестов в выбранном формате.
+/// В формате JSON Lines в stdout идут только записи о проверках, сводка выводится в stderr.
+pub struct Printer {
+    format: OutputFormat,
+}
+
+impl Printer {
+    pub fn new(format: OutputFormat) -> Printer {
+        Printer { format }
+    }
+
+    pub fn project_begin(&self, project: &ProjectInfo) {
+        if self.format == OutputFormat::Text {
+            println!("Begin project: {}", project.name.blue());
+        }
+    }
+
+    pub fn scenario_begin(&self, scenario: &Scenario) {
+        if self.format == OutputFormat::Text {
+            println!("Begin scenario: {}", scenario.name.blue());
+        }
+    }
+
+    pub fn case_finished(&self, case: &CaseResult) {
+        match self.format {
+            OutputFormat::Text => print_case_text(case),
+            OutputFormat::Jsonl => {
+                if let Some(record) = case.record() {
+                    println!("{}", record.to_json_line());
+                }
+            }
+        }
+    }
+
+    pub fn summary(&self, results: &[ProjectResult], total_duration: Duration) {
+        let result = match self.format {
+            OutputFormat::Text => print_summary(&mut std::io::stdout(), results, total_duration),
+            OutputFormat::Jsonl => print_summary(&mut std::io::stderr(), results, total_duration),
+        };
+        // Ошибку вывода в консоль обработать все равно не получится
+        result.ok();
+    }
+}
+
+/// Выводим результат проверки в человекочитаемом виде
+fn print_case_text(case: &CaseResult) {
+    let CaseResult {
+        index,
+        scenario,
+        project,
+        test,
+        outcome,
+        attempts,
+        ..
+    } = case;
+
+    // Повторы показываем только если они были
+    let retries = match outcome {
+        CaseOutcome::Passed(_) if case.is_flaky() => {
+            format!(", {} after {} attempts", "flaky".yellow(), attempts)
+        }
+        CaseOutcome::Failed(_) if *attempts > 1 => format!(", attempts {attempts}"),
+        _ => String::new(),
+    };
+
+    match scenario {
+        None => {
+            // Имя есть только у именованных тестов и тестов из матрицы
+            let name = match test.name.as_deref() {
+                Some(name) => format!(r#", name "{name}""#),
+                None => String::new(),
+            };
+
+            match outcome {
+                CaseOutcome::Passed(_) => {
+                    println!(
+                        r#"{}: test number "{}"{}, order_id: "{}", platform "{}"{}"#,
+                        "Test passed".green(),
+                        index,
+                        name,
+                        test.purchase.order_id,
+                        test.purchase.platform,
+                        retries
+                    );
+                }
+                CaseOutcome::Failed(err) => {
+                    eprintln!(
+                        r#"{}: test number "{}"{}, order_id: "{}", platform "{}"{}, err: "{err:#}""#,
+                        "Test failed".red(),
+                        index,
+                        name,
+                        test.purchase.order_id,
+                        test.purchase.platform,
+                        retries
+                    );
+                }
+                CaseOutcome::Skipped => {
+                    println!(
+                        r#"{}: test number "{}"{}, order_id: "{}", platform "{}""#,
+                        "Test skipped".yellow(),
+                        index,
+                        name,
+                        test.purchase.order_id,
+                        test.purchase.platform
+                    );
+                }
+            }
+        }
+        Some(_) => {
+            let name = test.name.as_deref().unwrap_or_default();
+
+            match outcome {
+                CaseOutcome::Passed(_) => {
+                    println!(
+                        r#"{}: step "{}", project "{}", order_id: "{}", platform "{}"{}"#,
+                        "Step passed".green(),
+                        name,
+                        project.name,
+                        test.purchase.order_id,
+                        test.purchase.platform,
+                        retries
+                    );
+                }
+                CaseOutcome::Failed(err) => {
+                    eprintln!(
+                        r#"{}: step "{}", project "{}", order_id: "{}", platform "{}"{}, err: "{err:#}""#,
+                        "Step failed".red(),
+                        name,
+                        project.name,
+                        test.purchase.order_id,
+                        test.purchase.platform,
+                        retries
+                    );
+                }
+                CaseOutcome::Skipped => {
+                    println!(
+                        r#"{}: step "{}", project "{}", order_id: "{}", platform "{}""#,
+                        "Step skipped".yellow(),
+                        name,
+                        project.name,
+                        test.purchase.order_id,
+                        test.purchase.platform
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use owo_colors::OwoColorize;
+use std::{io::Write, path::Path, time::Duration};
+use validate_lib::{
+    CheckError, CheckRecord, Config, ProjectInfo, PurchaseResponse, PurchaseStatus, TestCase,
+};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Итог выполнения одной проверки
+#[derive(Debug)]
+pub enum CaseOutcome {
+    Passed(PurchaseResponse),
+    Failed(eyre::Error),
+    Skipped,
+}
+
+impl CaseOutcome {
+    /// Результат запроса, если проверка выполнялась
+    pub fn result(&self) -> Option<Result<&PurchaseResponse, &eyre::Error>> {
+        match self {
+            CaseOutcome::Passed(response) => Some(Ok(response)),
+            CaseOutcome::Failed(err) => Some(Err(err)),
+            CaseOutcome::Skipped => None,
+        }
+    }
+}
+
+/// Результат одного теста или шага сценария
+#[derive(Debug)]
+pub struct CaseResult<'a> {
+    /// Номер теста в конфиге или шага в сценарии, начиная с 1
+    pub index: usize,
+    pub scenario: Option<&'a str>,
+    pub project: &'a ProjectInfo,
+    pub test: &'a TestCase,
+    pub outcome: CaseOutcome,
+    pub duration: Duration,
+    /// Количество выполненных попыток с учетом повторов
+    pub attempts: u32,
+}
+
+impl<'a> CaseResult<'a> {
+    /// Описание проверки для вывода
+    pub fn title(&self) -> String {
+        let mut title = match self.scenario {
+            Some(scenario) => format!(r#"scenario "{}", step {}"#, scenario, self.index),
+            None => format!("test number {}", self.index),
+        };
+        if let Some(name) = &self.test.name {
+            title.push_str(&format!(r#", name "{name}""#));
+        }
+        title
+    }
+
+    /// Проверка прошла не с первой попытки
+    pub fn is_flaky(&self) -> bool {
+        matches!(self.outcome, CaseOutcome::Passed(_)) && self.attempts > 1
+    }
+
+    /// Расшифрованный ответ сервера, если он был получен
+    pub fn purchase_status(&self) -> Option<&PurchaseStatus> {
+        match &self.outcome {
+            CaseOutcome::Passed(response) => Some(&response.purchase_status),
+            CaseOutcome::Failed(err) => match CheckError::find(err) {
+                Some(CheckError::StatusMismatch { response, .. }) => {
+                    Some(&response.purchase_status)
+                }
+                _ => None,
+            },
+            CaseOutcome::Skipped => None,
+        }
+    }
+
+    /// Машиночитаемая запись о проверке, если она выполнялась
+    pub fn record(&self) -> Option<CheckRecord> {
+        let result = self.outcome.result()?;
+        let mut record =
+            CheckRecord::new(self.project, self.index, self.test, result, self.duration);
+        record.scenario = self.scenario.map(str::to_owned);
+        record.attempts = self.attempts;
+        Some(record)
+    }
+}
+
+/// Краткое описание запроса для вывода, сам чек слишком большой
+pub fn request_summary(project: &ProjectInfo, test: &TestCase) -> Vec<(&'static str, String)> {
+    let purchase = &test.purchase;
+    vec![
+        ("Project", project.name.clone()),
+        ("Platform", purchase.platform.clone()),
+        ("Product id", purchase.product_id.clone()),
+        ("Order id", purchase.order_id.clone()),
+        (
+            "Client identifier",
+            purchase.client_identifier.clone().unwrap_or_default(),
+        ),
+        ("Receipt length", purchase.receipt.len().to_string()),
+        (
+            "Receipt signature",
+            if purchase.receipt_signature.is_some() {
+                "yes".to_owned()
+            } else {
+                "no".to_owned()
+            },
+        ),
+        ("Expected status", test.response.status.clone()),
+    ]
+}
+
+/// Результаты всех проверок одного конфига
+#[derive(Debug)]
+pub struct ProjectResult<'a> {
+    pub config: &'a Config,
+    pub config_path: &'a Path,
+    pub cases: Vec<CaseResult<'a>>,
+    pub duration: Duration,
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Количество проверок по итогам
+#[derive(Debug, Default, Clone, Copy)]
+pub struct OutcomeCounts {
+    pub passed: usize,
+    /// Прошедшие не с первой попытки, входят в число прошедших
+    pub flaky: usize,
+    pub failed: usize,
+    pub skipped: usize,
+}
+
+impl OutcomeCounts {
+    pub fn from_cases<'a, 'b: 'a>(
+        cases: impl IntoIterator<Item = &'a CaseResult<'b>>,
+    ) -> OutcomeCounts {
+        let mut counts = OutcomeCounts::default();
+        for case in cases {
+            match case.outcome {
+                CaseOutcome::Passed(_) => {
+                    counts.passed += 1;
+                    if case.is_flaky() {
+                        counts.flaky += 1;
+                    }
+                }
+                CaseOutcome::Failed(_) => counts.failed += 1,
+                CaseOutcome::Skipped => counts.skipped += 1,
+            }
+        }
+        counts
+    }
+
+    pub fn total(&self) -> usize {
+        self.passed + self.failed + self.skipped
+    }
+}
+
+/// Выводим итоговую сводку по проектам и в целом, а также список упавших проверок
+pub fn print_summary(
+    out: &mut impl Write,
+    results: &[ProjectResult],
+    total_duration: Duration,
+) -> std::io::Result<()> {
+    fn counts_text(counts: &OutcomeCounts) -> String {
+        let flaky = if counts.flaky > 0 {
+            format!(" (flaky {})", counts.flaky.yellow())
+        } else {
+            String::new()
+        };
+        format!(
+            "passed {}{}, failed {}, skipped {}",
+            counts.passed.green(),
+            flaky,
+            counts.failed.red(),
+            counts.skipped.yellow()
+        )
+    }
+
+    let failed_cases: Vec<(&CaseResult, &eyre::Error)> = results
+        .iter()
+        .flat_map(|result| result.cases.iter())
+        .filter_map(|case| match &case.outcome {
+            CaseOutcome::Failed(err) => Some((case, err)),
+            _ => None,
+        })
+        .collect();
+    if !failed_cases.is_empty() {
+        writeln!(out, "{}", "Failed:".bold())?;
+        for (case, err) in failed_cases {
+            writeln!(
+                out,
+                r#"  {}: {}, order_id: "{}", platform "{}", err: "{err:#}""#,
+                case.project.name.blue(),
+                case.title(),
+                case.test.purchase.order_id,
+                case.test.purchase.platform
+            )?;
+        }
+    }
+
+    writeln!(out, "{}", "Summary:".bold())?;
+    for result in results.iter() {
+        let counts = OutcomeCounts::from_cases(result.cases.iter());
+        writeln!(
+            out,
+            "  {}: {} in {:.2}s",
+            result.config.project.name.blue(),
+            counts_text(&counts),
+            result.duration.as_secs_f64()
+        )?;
+    }
+
+    let counts = OutcomeCounts::from_cases(results.iter().flat_map(|result| result.cases.iter()));
+    writeln!(
+        out,
+        "  {}: {} of {} in {:.2}s",
+        "Total".bold(),
+        counts_text(&counts),
+        counts.total(),
+        total_duration.as_secs_f64()
+    )
+}
@@ -0,0 +1,391 @@
+use crate::{
+    printer::Printer,
+    results::{CaseOutcome, CaseResult, ProjectResult},
+    state::{canonical_config_path, RunState},
+};
+use futures::StreamExt;
+use owo_colors::OwoColorize;
+use reqwest::Client;
+use slog::{debug, trace, Logger};
+use std::{
+    collections::BTreeMap,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+use validate_lib::{check_purchase, Config, ProjectInfo, Scenario, TestCase};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Задержка перед первым повтором упавшей проверки, дальше удваивается
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Параметры прогона функциональных тестов
+pub struct RunOptions<'s> {
+    /// Максимальное количество одновременно выполняемых проверок
+    pub jobs: usize,
+
+    /// Останавливаемся после первой упавшей проверки
+    pub fail_fast: bool,
+
+    /// Количество повторов упавшей проверки
+    pub retries: u32,
+
+    /// Выполняем только проверки, упавшие при прошлом запуске
+    pub rerun_state: Option<&'s RunState>,
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Единица параллельного выполнения: отдельный тест или сценарий целиком
+#[derive(Clone, Copy)]
+pub enum WorkItem<'a> {
+    Test {
+        config_index: usize,
+        index: usize,
+        test: &'a TestCase,
+    },
+    Scenario {
+        config_index: usize,
+        scenario: &'a Scenario,
+    },
+}
+
+/// Результат выполнения единицы работы
+pub struct CompletedItem<'a> {
+    pub config_index: usize,
+    pub scenario: Option<&'a Scenario>,
+    pub cases: Vec<CaseResult<'a>>,
+    pub begin_time: Instant,
+    pub end_time: Instant,
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Выполняем проверку, при ошибке повторяем ее с растущей задержкой.
+/// Возвращаем итог и количество выполненных попыток.
+async fn check_with_retries(
+    logger: &Logger,
+    http_client: &Client,
+    project: &ProjectInfo,
+    test: &TestCase,
+    retries: u32,
+) -> (CaseOutcome, u32) {
+    let mut attempt = 1;
+    loop {
+        match check_purchase(logger, http_client, project, test).await {
+            Ok(response) => return (CaseOutcome::Passed(response), attempt),
+            Err(err) if attempt > retries => return (CaseOutcome::Failed(err), attempt),
+            Err(err) => {
+                let delay = RETRY_BASE_DELAY * 2_u32.pow(attempt - 1);
+                debug!(logger, "Check failed, retry after {:?}: {:#}", delay, err);
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+/// Выполняем отдельный тест
+async fn execute_test<'a>(
+    logger: &Logger,
+    http_client: &Client,
+    config: &'a Config,
+    index: usize,
+    test: &'a TestCase,
+    retries: u32,
+) -> CaseResult<'a> {
+    // Создаем логирование для данной задачи с контекстом
+    let logger = logger.new(
+        slog::o!("index" => format!("{}", index), "product" => test.purchase.product_id.clone()),
+    );
+
+    trace!(logger, "Test start");
+
+    let begin_time = Instant::now();
+    let (outcome, attempts) =
+        check_with_retries(&logger, http_client, &config.project, test, retries).await;
+
+    CaseResult {
+        index,
+        scenario: None,
+        project: &config.project,
+        test,
+        outcome,
+        duration: begin_time.elapsed(),
+        attempts,
+    }
+}
+
+/// Выполняем сценарий, шаги идут строго по порядку.
+/// Шаги не повторяются: упавший шаг мог уже изменить состояние покупки на сервере,
+/// и повтор получил бы другой статус.
+async fn execute_scenario<'a>(
+    logger: &Logger,
+    http_client: &Client,
+    config: &'a Config,
+    scenario: &'a Scenario,
+) -> Vec<CaseResult<'a>> {
+    // Создаем логирование для данного сценария с контекстом
+    let logger = logger.new(slog::o!("scenario" => scenario.name.clone()));
+
+    // После первой ошибки остальные шаги не имеют смысла
+    let mut failed = false;
+    let mut results = Vec::with_capacity(scenario.steps.len());
+    for (i, step) in scenario.steps.iter().enumerate() {
+        let test = &step.test;
+        let project = step.project(&config.project);
+
+        let begin_time = Instant::now();
+        let (outcome, attempts) = if failed {
+            (CaseOutcome::Skipped, 0)
+        } else {
+            let logger = logger.new(slog::o!("step" => test.name.clone()));
+            trace!(logger, "Step start");
+
+            let (outcome, attempts) =
+                check_with_retries(&logger, http_client, project, test, 0).await;
+            failed = matches!(outcome, CaseOutcome::Failed(_));
+            (outcome, attempts)
+        };
+
+        results.push(CaseResult {
+            index: i + 1,
+            scenario: Some(&scenario.name),
+            project,
+            test,
+            outcome,
+            duration: begin_time.elapsed(),
+            attempts,
+        });
+    }
+    results
+}
+
+/// Выполняем тест или сценарий целиком
+pub async fn execute_item<'a>(
+    logger: &Logger,
+    http_client: &Client,
+    configs: &'a [Config],
+    item: WorkItem<'a>,
+    retries: u32,
+) -> CompletedItem<'a> {
+    let begin_time = Instant::now();
+    let (config_index, scenario, cases) = match item {
+        WorkItem::Test {
+            config_index,
+            index,
+            test,
+        } => {
+            let config = &configs[config_index];
+            let case = execute_test(logger, http_client, config, index, test, retries).await;
+            (config_index, None, vec![case])
+        }
+        WorkItem::Scenario {
+            config_index,
+            scenario,
+        } => {
+            let config = &configs[config_index];
+            let cases = execute_scenario(logger, http_client, config, scenario).await;
+            (config_index, Some(scenario), cases)
+        }
+    };
+    CompletedItem {
+        config_index,
+        scenario,
+        cases,
+        begin_time,
+        end_time: Instant::now(),
+    }
+}
+
+/// Пропущенные после остановки проверки, чтобы итоги учитывали все отобранные проверки
+fn skipped_cases<'a>(configs: &'a [Config], item: WorkItem<'a>) -> (usize, Vec<CaseResult<'a>>) {
+    let skipped = |index, scenario, project, test| CaseResult {
+        index,
+        scenario,
+        project,
+        test,
+        outcome: CaseOutcome::Skipped,
+        duration: Duration::ZERO,
+        attempts: 0,
+    };
+    match item {
+        WorkItem::Test {
+            config_index,
+            index,
+            test,
+        } => {
+            let project = &configs[config_index].project;
+            (config_index, vec![skipped(index, None, project, test)])
+        }
+        WorkItem::Scenario {
+            config_index,
+            scenario,
+        } => {
+            let project = &configs[config_index].project;
+            let cases = scenario
+                .steps
+                .iter()
+                .enumerate()
+                .map(|(i, step)| {
+                    skipped(
+                        i + 1,
+                        Some(scenario.name.as_str()),
+                        step.project(project),
+                        &step.test,
+                    )
+                })
+                .collect();
+            (config_index, cases)
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Выполняем тесты и сценарии всех конфигов, одновременно выполняется не больше jobs проверок.
+/// Проверки завершаются в любом порядке, но результаты выводятся строго в порядке конфигов,
+/// тестов и сценариев.
+pub async fn run_functional_tests<'a>(
+    logger: &Logger,
+    http_client: &Client,
+    printer: &Printer,
+    configs: &'a [Config],
+    config_paths: &'a [PathBuf],
+    options: &RunOptions<'_>,
+) -> Vec<ProjectResult<'a>> {
+    // Упавшие проверки в состоянии сохранены с каноническими путями
+    let canonical_paths: Vec<PathBuf> = config_paths
+        .iter()
+        .map(|path| canonical_config_path(path))
+        .collect();
+
+    // Сначала тесты конфига, затем его сценарии
+    let items: Vec<WorkItem> = configs
+        .iter()
+        .zip(canonical_paths.iter())
+        .enumerate()
+        .flat_map(|(config_index, (config, config_path))| {
+            let tests = config
+                .tests
+                .iter()
+                .enumerate()
+                .map(move |(i, test)| WorkItem::Test {
+                    config_index,
+                    index: i + 1,
+                    test,
+                })
+                .filter(move |item| match (options.rerun_state, item) {
+                    (Some(state), WorkItem::Test { index, test, .. }) => {
+                        state.is_test_failed(config_path, *index, test)
+                    }
+                    _ => true,
+                });
+            let scenarios = config
+                .scenarios
+                .iter()
+                .filter(move |scenario| match options.rerun_state {
+                    Some(state) => state.is_scenario_failed(config_path, scenario),
+                    None => true,
+                })
+                .map(move |scenario| WorkItem::Scenario {
+                    config_index,
+                    scenario,
+                });
+            tests.chain(scenarios)
+        })
+        .collect();
+
+    let mut results: Vec<ProjectResult> = configs
+        .iter()
+        .zip(config_paths.iter())
+        .map(|(config, config_path)| ProjectResult {
+            config,
+            config_path,
+            cases: Vec::new(),
+            duration: Duration::ZERO,
+        })
+        .collect();
+
+    // Время начала первой и окончания последней проверки каждого проекта
+    let mut project_spans: Vec<Option<(Instant, Instant)>> = vec![None; configs.len()];
+
+    // Выводим завершенную проверку, возвращаем ее результаты для добавления к проекту
+    let mut current_config = None;
+    let mut finish_item = |completed: CompletedItem<'a>| {
+        let config_index = completed.config_index;
+
+        if current_config != Some(config_index) {
+            printer.project_begin(&configs[config_index].project);
+            current_config = Some(config_index);
+        }
+        if let Some(scenario) = completed.scenario {
+            printer.scenario_begin(scenario);
+        }
+
+        for case in completed.cases.iter() {
+            printer.case_finished(case);
+        }
+
+        let span =
+            project_spans[config_index].get_or_insert((completed.begin_time, completed.end_time));
+        span.0 = span.0.min(completed.begin_time);
+        span.1 = span.1.max(completed.end_time);
+
+        (config_index, completed.cases)
+    };
+
+    // Медленная проверка не должна занимать остальные слоты, поэтому порядок восстанавливаем сами
+    let mut completed_stream = futures::stream::iter(items.iter().copied().enumerate())
+        .map(|(item_index, item)| async move {
+            let completed = execute_item(logger, http_client, configs, item, options.retries).await;
+            (item_index, completed)
+        })
+        .buffer_unordered(options.jobs);
+
+    // Завершенные раньше предыдущих проверки ждут своей очереди на вывод
+    let mut pending: BTreeMap<usize, CompletedItem> = BTreeMap::new();
+    let mut next_item = 0;
+    while let Some((item_index, completed)) = completed_stream.next().await {
+        let failed = completed
+            .cases
+            .iter()
+            .any(|case| matches!(case.outcome, CaseOutcome::Failed(_)));
+        pending.insert(item_index, completed);
+
+        while let Some(completed) = pending.remove(&next_item) {
+            let (config_index, cases) = finish_item(completed);
+            results[config_index].cases.extend(cases);
+            next_item += 1;
+        }
+
+        // Выполняющиеся в этот момент проверки отменяются вместе с потоком
+        if failed && options.fail_fast {
+            eprintln!(
+                "{}",
+                "Fail fast: remaining checks are not executed".yellow()
+            );
+            break;
+        }
+    }
+
+    drop(completed_stream);
+
+    // После остановки выводим уже завершенные проверки, которые ждали предыдущих,
+    // а не начатые и отмененные учитываем как пропущенные
+    for (item_index, item) in items.iter().enumerate().skip(next_item) {
+        let (config_index, cases) = match pending.remove(&item_index) {
+            Some(completed) => finish_item(completed),
+            None => skipped_cases(configs, *item),
+        };
+        results[config_index].cases.extend(cases);
+    }
+
+    for (result, span) in results.iter_mut().zip(project_spans) {
+        if let Some((begin_time, end_time)) = span {
+            result.duration = end_time.duration_since(begin_time);
+        }
+    }
+
+    results
+}
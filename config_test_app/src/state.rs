@@ -0,0 +1,384 @@
+use crate::results::{CaseOutcome, ProjectResult};
+use eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use validate_lib::{Scenario, TestCase};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Упавшая проверка. Сценарии перезапускаются целиком, так как шаги зависят друг от друга
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum FailedCheck {
+    Test {
+        config: PathBuf,
+        index: usize,
+        name: Option<String>,
+    },
+    Scenario {
+        config: PathBuf,
+        name: String,
+    },
+}
+
+impl FailedCheck {
+    fn config(&self) -> &Path {
+        let (FailedCheck::Test { config, .. } | FailedCheck::Scenario { config, .. }) = self;
+        config
+    }
+
+    /// Есть ли еще проверка в конфиге и выполнялась ли она в этом запуске
+    fn status_in(&self, result: &ProjectResult) -> (bool, bool) {
+        let executed_cases = result
+            .cases
+            .iter()
+            .filter(|case| !matches!(case.outcome, CaseOutcome::Skipped));
+        match self {
+            FailedCheck::Test { index, name, .. } => {
+                let exists = matches!(
+                    index.checked_sub(1).and_then(|i| result.config.tests.get(i)),
+                    Some(test) if test.name == *name
+                );
+                let executed = executed_cases.into_iter().any(|case| {
+                    case.scenario.is_none() && case.index == *index && case.test.name == *name
+                });
+                (exists, executed)
+            }
+            FailedCheck::Scenario { name, .. } => {
+                let exists = result
+                    .config
+                    .scenarios
+                    .iter()
+                    .any(|scenario| scenario.name == *name);
+                let executed = executed_cases
+                    .into_iter()
+                    .any(|case| case.scenario == Some(name.as_str()));
+                (exists, executed)
+            }
+        }
+    }
+}
+
+/// Путь к конфигу для сравнения: один и тот же файлик может быть передан по разным путям
+pub fn canonical_config_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+/// Состояние последнего запуска функциональных тестов
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RunState {
+    pub failed: Vec<FailedCheck>,
+}
+
+impl RunState {
+    /// Собираем упавшие проверки по результатам запуска.
+    /// Упавшие ранее проверки, которые в этот раз не выполнялись, переносим из прошлого состояния,
+    /// иначе после остановки по первой ошибке или частичного перезапуска они бы потерялись.
+    pub fn from_results(results: &[ProjectResult], previous: Option<&RunState>) -> RunState {
+        let mut failed = Vec::new();
+        for result in results.iter() {
+            let failed_cases = result
+                .cases
+                .iter()
+                .filter(|case| matches!(case.outcome, CaseOutcome::Failed(_)));
+            for case in failed_cases {
+                let check = match case.scenario {
+                    Some(scenario) => FailedCheck::Scenario {
+                        config: canonical_config_path(result.config_path),
+                        name: scenario.to_owned(),
+                    },
+                    None => FailedCheck::Test {
+                        config: canonical_config_path(result.config_path),
+                        index: case.index,
+                        name: case.test.name.clone(),
+                    },
+                };
+                if !failed.contains(&check) {
+                    failed.push(check);
+                }
+            }
+        }
+
+        let config_paths: Vec<PathBuf> = results
+            .iter()
+            .map(|result| canonical_config_path(result.config_path))
+            .collect();
+        for check in previous.iter().flat_map(|previous| previous.failed.iter()) {
+            let keep = match config_paths.iter().position(|path| path == check.config()) {
+                // Конфиг в этот раз не передавался
+                None => true,
+                // Удаленные из конфига проверки больше не перезапустить
+                Some(i) => matches!(check.status_in(&results[i]), (true, false)),
+            };
+            if keep && !failed.contains(check) {
+                failed.push(check.clone());
+            }
+        }
+
+        RunState { failed }
+    }
+
+    pub fn load(path: &Path) -> Result<RunState, eyre::Error> {
+        let file = std::fs::File::open(path)
+            .wrap_err_with(|| format!("State file {} open failed", path.display()))?;
+        let mut state: RunState = serde_json::from_reader(std::io::BufReader::new(file))
+            .wrap_err_with(|| format!("State file {} parse failed", path.display()))?;
+
+        // Файлики старых версий могут содержать относительные пути
+        for check in state.failed.iter_mut() {
+            let (FailedCheck::Test { config, .. } | FailedCheck::Scenario { config, .. }) = check;
+            *config = canonical_config_path(config);
+        }
+        Ok(state)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), eyre::Error> {
+        let text = serde_json::to_string_pretty(self).wrap_err("State serialize failed")?;
+        std::fs::write(path, text)
+            .wrap_err_with(|| format!("State file {} write failed", path.display()))
+    }
+
+    /// Относится ли хоть одна упавшая проверка к переданным конфигам, пути должны быть канонические
+    pub fn has_failures_for(&self, config_paths: &[PathBuf]) -> bool {
+        self.failed
+            .iter()
+            .any(|check| config_paths.iter().any(|path| path == check.config()))
+    }
+
+    /// Падал ли тест при последнем запуске, имя сверяем на случай изменения конфига.
+    /// Путь к конфигу должен быть каноническим.
+    pub fn is_test_failed(&self, config_path: &Path, index: usize, test: &TestCase) -> bool {
+        self.failed.iter().any(|check| match check {
+            FailedCheck::Test {
+                config,
+                index: failed_index,
+                name,
+            } => config == config_path && *failed_index == index && *name == test.name,
+            FailedCheck::Scenario { .. } => false,
+        })
+    }
+
+    /// Падал ли какой-то шаг сценария при последнем запуске, путь к конфигу канонический
+    pub fn is_scenario_failed(&self, config_path: &Path, scenario: &Scenario) -> bool {
+        self.failed.iter().any(|check| match check {
+            FailedCheck::Scenario { config, name } => {
+                config == config_path && *name == scenario.name
+            }
+            FailedCheck::Test { .. } => false,
+        })
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::results::CaseResult;
+    use reqwest::StatusCode;
+    use std::time::Duration;
+    use validate_lib::{
+        Config, LoadSettings, ProjectInfo, PurchaseData, PurchaseResponse, PurchaseStatus,
+        ResponseData, ScenarioStep,
+    };
+
+    fn test_case(name: &str) -> TestCase {
+        TestCase {
+            name: Some(name.to_owned()),
+            purchase: PurchaseData {
+                platform: "google".to_owned(),
+                product_id: "product".to_owned(),
+                order_id: name.to_owned(),
+                receipt: "receipt".to_owned(),
+                receipt_signature: None,
+                client_identifier: None,
+            },
+            response: ResponseData {
+                status: "ok".to_owned(),
+            },
+            weight: 1.0,
+        }
+    }
+
+    /// Конфиг с тестами a, b, c и сценарием s из двух шагов
+    fn config() -> Config {
+        Config {
+            project: ProjectInfo {
+                api_url: "http://127.0.0.1/validate".parse().unwrap(),
+                secret_key: "secret".to_owned(),
+                name: "project".to_owned(),
+            },
+            tests: vec![test_case("a"), test_case("b"), test_case("c")],
+            scenarios: vec![Scenario {
+                name: "s".to_owned(),
+                steps: vec![
+                    ScenarioStep {
+                        project: None,
+                        test: test_case("s1"),
+                    },
+                    ScenarioStep {
+                        project: None,
+                        test: test_case("s2"),
+                    },
+                ],
+            }],
+            load: LoadSettings::default(),
+        }
+    }
+
+    fn passed() -> CaseOutcome {
+        CaseOutcome::Passed(PurchaseResponse {
+            http_status: StatusCode::OK,
+            purchase_status: PurchaseStatus {
+                status: "ok".to_owned(),
+                description: None,
+                payload: None,
+            },
+        })
+    }
+
+    fn failed() -> CaseOutcome {
+        CaseOutcome::Failed(eyre::eyre!("failed"))
+    }
+
+    fn test_result(config: &Config, index: usize, outcome: CaseOutcome) -> CaseResult<'_> {
+        CaseResult {
+            index,
+            scenario: None,
+            project: &config.project,
+            test: &config.tests[index - 1],
+            outcome,
+            duration: Duration::ZERO,
+            attempts: 1,
+        }
+    }
+
+    fn step_result(config: &Config, index: usize, outcome: CaseOutcome) -> CaseResult<'_> {
+        let scenario = &config.scenarios[0];
+        CaseResult {
+            index,
+            scenario: Some(&scenario.name),
+            project: &config.project,
+            test: &scenario.steps[index - 1].test,
+            outcome,
+            duration: Duration::ZERO,
+            attempts: 1,
+        }
+    }
+
+    fn failed_test(config: &str, index: usize, name: &str) -> FailedCheck {
+        FailedCheck::Test {
+            config: PathBuf::from(config),
+            index,
+            name: Some(name.to_owned()),
+        }
+    }
+
+    fn failed_scenario(config: &str, name: &str) -> FailedCheck {
+        FailedCheck::Scenario {
+            config: PathBuf::from(config),
+            name: name.to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_from_results_collects_failures() {
+        let config = config();
+        let results = [ProjectResult {
+            config: &config,
+            config_path: Path::new("missing/a.yml"),
+            cases: vec![
+                test_result(&config, 1, passed()),
+                test_result(&config, 2, failed()),
+                test_result(&config, 3, CaseOutcome::Skipped),
+                step_result(&config, 1, failed()),
+                step_result(&config, 2, CaseOutcome::Skipped),
+            ],
+            duration: Duration::ZERO,
+        }];
+
+        let state = RunState::from_results(&results, None);
+        assert_eq!(
+            state.failed,
+            [
+                failed_test("missing/a.yml", 2, "b"),
+                failed_scenario("missing/a.yml", "s")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_from_results_keeps_not_executed_failures() {
+        let config = config();
+        let previous = RunState {
+            failed: vec![
+                // Выполнен и прошел
+                failed_test("missing/a.yml", 1, "a"),
+                // Пропущен после остановки
+                failed_test("missing/a.yml", 2, "b"),
+                // Не отбирался для выполнения
+                failed_scenario("missing/a.yml", "s"),
+                // Удален из конфига
+                failed_test("missing/a.yml", 3, "old"),
+                // Другой конфиг
+                failed_test("missing/other.yml", 1, "a"),
+            ],
+        };
+        let results = [ProjectResult {
+            config: &config,
+            config_path: Path::new("missing/a.yml"),
+            cases: vec![
+                test_result(&config, 1, passed()),
+                test_result(&config, 3, failed()),
+                test_result(&config, 2, CaseOutcome::Skipped),
+            ],
+            duration: Duration::ZERO,
+        }];
+
+        let state = RunState::from_results(&results, Some(&previous));
+        assert_eq!(
+            state.failed,
+            [
+                failed_test("missing/a.yml", 3, "c"),
+                failed_test("missing/a.yml", 2, "b"),
+                failed_scenario("missing/a.yml", "s"),
+                failed_test("missing/other.yml", 1, "a"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_is_test_failed() {
+        let config = config();
+        let state = RunState {
+            failed: vec![
+                failed_test("missing/a.yml", 2, "b"),
+                failed_scenario("missing/a.yml", "b"),
+            ],
+        };
+        let path = Path::new("missing/a.yml");
+        assert!(state.is_test_failed(path, 2, &config.tests[1]));
+        assert!(!state.is_test_failed(path, 1, &config.tests[0]));
+        // Тест на этом месте переименован
+        assert!(!state.is_test_failed(path, 2, &config.tests[2]));
+        assert!(!state.is_test_failed(Path::new("missing/b.yml"), 2, &config.tests[1]));
+    }
+
+    #[test]
+    fn test_is_scenario_failed() {
+        let config = config();
+        let scenario = &config.scenarios[0];
+        let path = Path::new("missing/a.yml");
+
+        let state = RunState {
+            failed: vec![failed_scenario("missing/a.yml", "s")],
+        };
+        assert!(state.is_scenario_failed(path, scenario));
+        assert!(!state.is_scenario_failed(Path::new("missing/b.yml"), scenario));
+
+        let state = RunState {
+            failed: vec![failed_test("missing/a.yml", 1, "s")],
+        };
+        assert!(!state.is_scenario_failed(path, scenario));
+    }
+}
@@ -0,0 +1,517 @@
+use crate::{
+    results::{request_summary, CaseOutcome, CaseResult},
+    runner::{execute_item, CompletedItem, WorkItem},
+};
+use crossterm::{
+    event::{Event, EventStream, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use eyre::WrapErr;
+use futures::{stream::FuturesUnordered, StreamExt};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use reqwest::Client;
+use slog::Logger;
+use std::collections::VecDeque;
+use validate_lib::{Config, ProjectInfo, Scenario, TestCase};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Состояние проверки в списке
+enum RowState<'a> {
+    Idle,
+    Queued,
+    Running,
+    Done(CaseResult<'a>),
+}
+
+/// Строка списка: отдельный тест или шаг сценария
+struct Row<'a> {
+    config_index: usize,
+    scenario: Option<&'a Scenario>,
+    /// Номер теста в конфиге или шага в сценарии, начиная с 1
+    index: usize,
+    project: &'a ProjectInfo,
+    test: &'a TestCase,
+    selected: bool,
+    state: RowState<'a>,
+}
+
+impl<'a> Row<'a> {
+    fn label(&self) -> String {
+        let name = self.test.name.as_deref();
+        match (self.scenario, name) {
+            (Some(scenario), Some(name)) => {
+                format!("{} {}/{}", self.project.name, scenario.name, name)
+            }
+            (Some(scenario), None) => {
+                format!(
+                    "{} {}/step {}",
+                    self.project.name, scenario.name, self.index
+                )
+            }
+            (None, Some(name)) => format!("{} #{} {}", self.project.name, self.index, name),
+            (None, None) => format!(
+                "{} #{} {}",
+                self.project.name, self.index, self.test.purchase.order_id
+            ),
+        }
+    }
+
+    fn status(&self) -> Span<'static> {
+        match &self.state {
+            RowState::Idle => Span::raw("    "),
+            RowState::Queued => Span::styled("wait", Style::default().fg(Color::DarkGray)),
+            RowState::Running => Span::styled("run ", Style::default().fg(Color::Cyan)),
+            RowState::Done(case) => match case.outcome {
+                CaseOutcome::Passed(_) if case.is_flaky() => {
+                    Span::styled("flky", Style::default().fg(Color::Yellow))
+                }
+                CaseOutcome::Passed(_) => Span::styled("pass", Style::default().fg(Color::Green)),
+                CaseOutcome::Failed(_) => Span::styled("FAIL", Style::default().fg(Color::Red)),
+                CaseOutcome::Skipped => Span::styled("skip", Style::default().fg(Color::Yellow)),
+            },
+        }
+    }
+
+    /// Шаги сценария по отдельности не выполняются, только сценарий целиком
+    fn work_item(&self) -> WorkItem<'a> {
+        match self.scenario {
+            Some(scenario) => WorkItem::Scenario {
+                config_index: self.config_index,
+                scenario,
+            },
+            None => WorkItem::Test {
+                config_index: self.config_index,
+                index: self.index,
+                test: self.test,
+            },
+        }
+    }
+
+    fn is_busy(&self) -> bool {
+        matches!(self.state, RowState::Queued | RowState::Running)
+    }
+
+    /// Относится ли строка к единице работы
+    fn belongs_to(&self, item: &WorkItem) -> bool {
+        match (item, self.scenario) {
+            (
+                WorkItem::Test {
+                    config_index,
+                    index,
+                    ..
+                },
+                None,
+            ) => self.config_index == *config_index && self.index == *index,
+            (
+                WorkItem::Scenario {
+                    config_index,
+                    scenario,
+                },
+                Some(row_scenario),
+            ) => self.config_index == *config_index && std::ptr::eq(*scenario, row_scenario),
+            _ => false,
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+struct App<'a> {
+    rows: Vec<Row<'a>>,
+    list_state: ListState,
+    /// Проверки, ожидающие свободного слота
+    queue: VecDeque<WorkItem<'a>>,
+}
+
+impl<'a> App<'a> {
+    fn new(configs: &'a [Config]) -> App<'a> {
+        let mut rows = Vec::new();
+        for (config_index, config) in configs.iter().enumerate() {
+            for (i, test) in config.tests.iter().enumerate() {
+                rows.push(Row {
+                    config_index,
+                    scenario: None,
+                    index: i + 1,
+                    project: &config.project,
+                    test,
+                    selected: false,
+                    state: RowState::Idle,
+                });
+            }
+            for scenario in config.scenarios.iter() {
+                for (i, step) in scenario.steps.iter().enumerate() {
+                    rows.push(Row {
+                        config_index,
+                        scenario: Some(scenario),
+                        index: i + 1,
+                        project: step.project(&config.project),
+                        test: &step.test,
+                        selected: false,
+                        state: RowState::Idle,
+                    });
+                }
+            }
+        }
+
+        let mut list_state = ListState::default();
+        if !rows.is_empty() {
+            list_state.select(Some(0));
+        }
+
+        App {
+            rows,
+            list_state,
+            queue: VecDeque::new(),
+        }
+    }
+
+    fn current(&self) -> Option<usize> {
+        self.list_state.selected()
+    }
+
+    fn move_cursor(&mut self, offset: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let current = self.current().unwrap_or(0) as isize;
+        let next = (current + offset).clamp(0, self.rows.len() as isize - 1);
+        self.list_state.select(Some(next as usize));
+    }
+
+    /// Ставим строки в очередь, сценарии добавляются один раз
+    fn enqueue(&mut self, row_indexes: impl IntoIterator<Item = usize>) {
+        for row_index in row_indexes {
+            if self.rows[row_index].is_busy() {
+                continue;
+            }
+            let item = self.rows[row_index].work_item();
+            for row in self.rows.iter_mut().filter(|row| row.belongs_to(&item)) {
+                row.state = RowState::Queued;
+            }
+            self.queue.push_back(item);
+        }
+    }
+
+    fn enqueue_selected(&mut self) {
+        let selected: Vec<usize> = (0..self.rows.len())
+            .filter(|i| self.rows[*i].selected)
+            .collect();
+        if selected.is_empty() {
+            self.enqueue(self.current());
+        } else {
+            self.enqueue(selected);
+        }
+    }
+
+    fn mark_running(&mut self, item: &WorkItem) {
+        for row in self.rows.iter_mut().filter(|row| row.belongs_to(item)) {
+            row.state = RowState::Running;
+        }
+    }
+
+    fn complete(&mut self, completed: CompletedItem<'a>) {
+        for case in completed.cases {
+            let row = self.rows.iter_mut().find(|row| {
+                row.config_index == completed.config_index
+                    && row.index == case.index
+                    && row.scenario.map(|scenario| scenario.name.as_str()) == case.scenario
+            });
+            if let Some(row) = row {
+                row.state = RowState::Done(case);
+            }
+        }
+    }
+
+    /// Обрабатываем нажатие, возвращаем false для выхода
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.move_cursor(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_cursor(1),
+            KeyCode::PageUp => self.move_cursor(-20),
+            KeyCode::PageDown => self.move_cursor(20),
+            KeyCode::Home => self.move_cursor(-(self.rows.len() as isize)),
+            KeyCode::End => self.move_cursor(self.rows.len() as isize),
+            KeyCode::Char(' ') => {
+                if let Some(current) = self.current() {
+                    self.rows[current].selected = !self.rows[current].selected;
+                    self.move_cursor(1);
+                }
+            }
+            KeyCode::Enter => self.enqueue(self.current()),
+            KeyCode::Char('r') => self.enqueue_selected(),
+            KeyCode::Char('a') => self.enqueue(0..self.rows.len()),
+            _ => {}
+        }
+        true
+    }
+
+    fn all_passed(&self) -> bool {
+        !self.rows.iter().any(|row| match &row.state {
+            RowState::Done(case) => matches!(case.outcome, CaseOutcome::Failed(_)),
+            _ => false,
+        })
+    }
+
+    //////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let vertical = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(frame.size());
+        let horizontal = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+            .split(vertical[0]);
+
+        let items: Vec<ListItem> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mark = if row.selected { "[x] " } else { "[ ] " };
+                ListItem::new(Line::from(vec![
+                    Span::raw(mark),
+                    row.status(),
+                    Span::raw(" "),
+                    Span::raw(row.label()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Cases"))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, horizontal[0], &mut self.list_state);
+
+        let details = Paragraph::new(self.details())
+            .block(Block::default().borders(Borders::ALL).title("Details"))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(details, horizontal[1]);
+
+        frame.render_widget(Paragraph::new(self.status_line()), vertical[1]);
+    }
+
+    /// Запрос, ответ и цепочка ошибок текущей проверки
+    fn details(&self) -> Vec<Line<'static>> {
+        let row = match self.current() {
+            Some(current) => &self.rows[current],
+            None => return vec![Line::raw("No test cases in configs")],
+        };
+
+        let bold = Style::default().add_modifier(Modifier::BOLD);
+        let mut lines = vec![Line::styled(row.label(), bold), Line::raw("")];
+        for (name, value) in request_summary(row.project, row.test) {
+            lines.push(Line::from(vec![
+                Span::styled(format!("{name}: "), bold),
+                Span::raw(value),
+            ]));
+        }
+        lines.push(Line::raw(""));
+
+        let case = match &row.state {
+            RowState::Idle => {
+                lines.push(Line::raw("Not executed"));
+                return lines;
+            }
+            RowState::Queued => {
+                lines.push(Line::raw("Queued"));
+                return lines;
+            }
+            RowState::Running => {
+                lines.push(Line::raw("Running..."));
+                return lines;
+            }
+            RowState::Done(case) => case,
+        };
+
+        let (verdict, color) = match case.outcome {
+            CaseOutcome::Passed(_) if case.is_flaky() => ("Flaky", Color::Yellow),
+            CaseOutcome::Passed(_) => ("Passed", Color::Green),
+            CaseOutcome::Failed(_) => ("Failed", Color::Red),
+            CaseOutcome::Skipped => ("Skipped", Color::Yellow),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(
+                verdict,
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(
+                " in {:.3}s, attempts {}",
+                case.duration.as_secs_f64(),
+                case.attempts
+            )),
+        ]));
+
+        if let Some(purchase_status) = case.purchase_status() {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled("Server response:", bold));
+            lines.push(Line::raw(format!("status: {}", purchase_status.status)));
+            lines.push(Line::raw(format!(
+                "description: {}",
+                purchase_status.description_text()
+            )));
+            lines.push(Line::raw(format!(
+                "payload: {}",
+                purchase_status.payload_text()
+            )));
+        }
+
+        if let CaseOutcome::Failed(err) = &case.outcome {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled("Error chain:", bold));
+            for (i, err) in err.chain().enumerate() {
+                lines.push(Line::styled(
+                    format!("{i}: {err}"),
+                    Style::default().fg(Color::Red),
+                ));
+            }
+        }
+
+        lines
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        let (mut passed, mut failed, mut skipped, mut busy) = (0, 0, 0, 0);
+        for row in self.rows.iter() {
+            match &row.state {
+                RowState::Done(case) => match case.outcome {
+                    CaseOutcome::Passed(_) => passed += 1,
+                    CaseOutcome::Failed(_) => failed += 1,
+                    CaseOutcome::Skipped => skipped += 1,
+                },
+                RowState::Queued | RowState::Running => busy += 1,
+                RowState::Idle => {}
+            }
+        }
+        Line::from(vec![
+            Span::styled(
+                format!("passed {passed} "),
+                Style::default().fg(Color::Green),
+            ),
+            Span::styled(format!("failed {failed} "), Style::default().fg(Color::Red)),
+            Span::styled(
+                format!("skipped {skipped} "),
+                Style::default().fg(Color::Yellow),
+            ),
+            Span::raw(format!("in progress {busy} | ")),
+            Span::styled(
+                "up/down move, space select, enter run, r run selected, a run all, q quit",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+async fn event_loop(
+    terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>,
+    logger: &Logger,
+    http_client: &Client,
+    configs: &[Config],
+    jobs: usize,
+    retries: u32,
+) -> Result<bool, eyre::Error> {
+    let mut app = App::new(configs);
+    let mut events = EventStream::new();
+    let mut running = FuturesUnordered::new();
+
+    loop {
+        // Запускаем проверки из очереди в пределах лимита
+        while running.len() < jobs {
+            let item = match app.queue.pop_front() {
+                Some(item) => item,
+                None => break,
+            };
+            app.mark_running(&item);
+            running.push(execute_item(logger, http_client, configs, item, retries));
+        }
+
+        terminal
+            .draw(|frame| app.draw(frame))
+            .wrap_err("Terminal draw failed")?;
+
+        tokio::select! {
+            event = events.next() => match event {
+                Some(Ok(Event::Key(key))) if key.kind == KeyEventKind::Press => {
+                    if !app.handle_key(key) {
+                        break;
+                    }
+                }
+                Some(Ok(_)) => {}
+                Some(Err(err)) => return Err(err).wrap_err("Terminal event read failed"),
+                None => break,
+            },
+            Some(completed) = running.next(), if !running.is_empty() => {
+                app.complete(completed);
+            }
+        }
+    }
+
+    Ok(app.all_passed())
+}
+
+/// Режим терминала для интерфейса, при панике восстанавливается в drop
+struct TerminalGuard {
+    active: bool,
+}
+
+impl TerminalGuard {
+    fn enter() -> Result<TerminalGuard, eyre::Error> {
+        enable_raw_mode().wrap_err("Terminal raw mode enable failed")?;
+        let guard = TerminalGuard { active: true };
+        execute!(std::io::stdout(), EnterAlternateScreen)
+            .wrap_err("Alternate screen enter failed")?;
+        Ok(guard)
+    }
+
+    /// Штатное восстановление терминала с проверкой ошибок
+    fn restore(mut self) -> Result<(), eyre::Error> {
+        self.active = false;
+        disable_raw_mode().wrap_err("Terminal raw mode disable failed")?;
+        execute!(std::io::stdout(), LeaveAlternateScreen).wrap_err("Alternate screen leave failed")
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        if self.active {
+            disable_raw_mode().ok();
+            execute!(std::io::stdout(), LeaveAlternateScreen).ok();
+        }
+    }
+}
+
+/// Интерактивный режим: список проверок, запуск выбранных и подробности по каждой.
+/// Возвращаем false, если какие-то из выполненных проверок упали.
+pub async fn execute_tui(
+    logger: &Logger,
+    http_client: &Client,
+    configs: &[Config],
+    jobs: usize,
+    retries: u32,
+) -> Result<bool, eyre::Error> {
+    let guard = TerminalGuard::enter()?;
+
+    let result = match Terminal::new(CrosstermBackend::new(std::io::stdout())) {
+        Ok(mut terminal) => {
+            event_loop(&mut terminal, logger, http_client, configs, jobs, retries).await
+        }
+        Err(err) => Err(err).wrap_err("Terminal create failed"),
+    };
+
+    // Терминал восстанавливаем в любом случае, при панике это сделает drop
+    guard.restore()?;
+
+    result
+}
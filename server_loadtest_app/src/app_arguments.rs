@@ -1,5 +1,162 @@
-use std::path::PathBuf;
+use crate::profile::LoadProfile;
+use std::{collections::BTreeMap, net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
 use structopt::StructOpt;
+use validate_lib::OutputFormat;
+
+/// Разбираем частоту запросов: 200/s, 6000/m или просто 200 запросов в секунду
+pub fn parse_rate_per_second(s: &str) -> Result<f64, eyre::Error> {
+    let (count, unit_seconds) = match s.split_once('/') {
+        Some((count, "s")) => (count, 1.0),
+        Some((count, "m")) => (count, 60.0),
+        Some((_, unit)) => {
+            return Err(eyre::eyre!(
+                "Unknown rate unit {}, only s/m are supported",
+                unit
+            ))
+        }
+        None => (s, 1.0),
+    };
+    let count: f64 = count
+        .trim()
+        .parse()
+        .map_err(|_| eyre::eyre!("Invalid rate value {}", s))?;
+    if !(count.is_finite() && count >= 0.0) {
+        return Err(eyre::eyre!("Rate cannot be negative"));
+    }
+    Ok(count / unit_seconds)
+}
+
+/// Разбираем длительность: 500ms, 30s, 5m, 1h или просто секунды
+pub fn parse_duration(s: &str) -> Result<Duration, eyre::Error> {
+    let s = s.trim();
+    let (value, unit_seconds) = if let Some(value) = s.strip_suffix("ms") {
+        (value, 0.001)
+    } else if let Some(value) = s.strip_suffix('s') {
+        (value, 1.0)
+    } else if let Some(value) = s.strip_suffix('m') {
+        (value, 60.0)
+    } else if let Some(value) = s.strip_suffix('h') {
+        (value, 3600.0)
+    } else {
+        (s, 1.0)
+    };
+    let value: f64 = value
+        .parse()
+        .map_err(|_| eyre::eyre!("Invalid duration {}", s))?;
+    if !(value.is_finite() && value >= 0.0) {
+        return Err(eyre::eyre!("Duration cannot be negative"));
+    }
+    Ok(Duration::from_secs_f64(value * unit_seconds))
+}
+
+/// Разбираем процент: 10% или просто 10
+pub fn parse_percent(s: &str) -> Result<f64, eyre::Error> {
+    let value = s.trim();
+    let value = value.strip_suffix('%').unwrap_or(value);
+    let value: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| eyre::eyre!("Invalid percent value {}", s))?;
+    if !(value.is_finite() && value >= 0.0) {
+        return Err(eyre::eyre!("Percent cannot be negative"));
+    }
+    Ok(value)
+}
+
+/// Requests rate of open model load
+#[derive(Debug, Clone, Copy)]
+pub struct Rate {
+    pub per_second: f64,
+}
+
+impl FromStr for Rate {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let per_second = parse_rate_per_second(s)?;
+        if per_second <= 0.0 {
+            return Err(eyre::eyre!("Rate must be positive"));
+        }
+        Ok(Rate { per_second })
+    }
+}
+
+/// Requests arrival schedule of open model load
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrivals {
+    /// Equal intervals between requests
+    Fixed,
+    /// Exponentially distributed intervals like independent clients
+    Poisson,
+}
+
+impl FromStr for Arrivals {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fixed" => Ok(Arrivals::Fixed),
+            "poisson" => Ok(Arrivals::Poisson),
+            _ => Err(eyre::eyre!(
+                "Unknown arrivals schedule {}, only fixed/poisson are supported",
+                s
+            )),
+        }
+    }
+}
+
+/// Test selection order for requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    /// Weighted interleaving with exact shares on any requests count
+    RoundRobin,
+    /// Independent weighted random choice for every request
+    Random,
+}
+
+impl FromStr for Selection {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round-robin" => Ok(Selection::RoundRobin),
+            "random" => Ok(Selection::Random),
+            _ => Err(eyre::eyre!(
+                "Unknown selection {}, only round-robin/random are supported",
+                s
+            )),
+        }
+    }
+}
+
+/// Relative weights of platforms in requests mix
+#[derive(Debug, Clone)]
+pub struct PlatformWeights(pub BTreeMap<String, f64>);
+
+impl FromStr for PlatformWeights {
+    type Err = eyre::Error;
+
+    /// Формат: "google=70,ios=25,windows=5"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = BTreeMap::new();
+        for item in s.split(',') {
+            let (platform, weight) = item
+                .split_once('=')
+                .ok_or_else(|| eyre::eyre!("Platform weight {} must be platform=weight", item))?;
+            let weight: f64 = weight
+                .trim()
+                .parse()
+                .map_err(|_| eyre::eyre!("Invalid platform weight {}", item))?;
+            if !(weight.is_finite() && weight >= 0.0) {
+                return Err(eyre::eyre!("Platform weight cannot be negative"));
+            }
+            if weights.insert(platform.trim().to_owned(), weight).is_some() {
+                return Err(eyre::eyre!("Platform {} weight is duplicated", platform));
+            }
+        }
+        Ok(PlatformWeights(weights))
+    }
+}
 
 /// App parameters
 #[derive(StructOpt, Debug)]
@@ -10,12 +167,93 @@ pub struct AppArguments {
     pub configs: Vec<PathBuf>,
 
     /// Number threads for requests
-    #[structopt(short, long)]
-    pub requests_parallel_threads: u32,
+    #[structopt(short = "t", long)]
+    pub requests_parallel_threads: Option<u32>,
 
     /// Requests count per thread
     #[structopt(short, long)]
-    pub requests_per_thread: u64,
+    pub requests_per_thread: Option<u64>,
+
+    /// Run duration with constant load instead of requests count, for example 5m
+    #[structopt(long, parse(try_from_str = parse_duration))]
+    pub duration: Option<Duration>,
+
+    /// Load profile stages duration:target, target is threads count or rate for open model,
+    /// for example "30s:10,2m:50,30s:0" or "1m:100/s,1m:500/s"
+    #[structopt(long)]
+    pub stages: Option<LoadProfile>,
+
+    /// Open model: send requests at fixed rate regardless of responses, for example 200/s
+    #[structopt(long)]
+    pub rate: Option<Rate>,
+
+    /// Requests arrival schedule for rate mode: fixed or poisson
+    #[structopt(long, default_value = "fixed")]
+    pub arrivals: Arrivals,
+
+    /// Total requests count for rate mode
+    #[structopt(long)]
+    pub requests: Option<u64>,
+
+    /// Maximum requests in flight for rate mode, scheduled requests above it are dropped
+    #[structopt(long, default_value = "1000")]
+    pub max_in_flight: usize,
+
+    /// Relative platform weights in requests mix, for example "google=70,ios=25,windows=5".
+    /// Config weight is set by load.weight in config and test weight by weight in test
+    #[structopt(long)]
+    pub platform_weights: Option<PlatformWeights>,
+
+    /// Test selection for requests: round-robin or random, both follow the weights
+    #[structopt(long, default_value = "round-robin")]
+    pub selection: Selection,
+
+    /// Maximum allowed share of failed requests, for example 5%, the run fails above it
+    #[structopt(long, parse(try_from_str = parse_percent))]
+    pub max_error_rate: Option<f64>,
+
+    /// Write per project, platform and test results breakdown to CSV file
+    #[structopt(long, parse(from_os_str))]
+    pub breakdown_csv: Option<PathBuf>,
+
+    /// Write full summary statistics to JSON file, it can be used as baseline later
+    #[structopt(long, parse(from_os_str))]
+    pub out: Option<PathBuf>,
+
+    /// Write per second metrics to CSV file
+    #[structopt(long, parse(from_os_str))]
+    pub timeseries: Option<PathBuf>,
+
+    /// Compare p50, p99, throughput and error rate with previous run JSON saved by --out,
+    /// requires --max-regression
+    #[structopt(long, requires = "max-regression", parse(from_os_str))]
+    pub baseline: Option<PathBuf>,
+
+    /// Maximum allowed regression against baseline, for example 10%, the run fails above it.
+    /// Error rate regression is measured in percentage points
+    #[structopt(long, requires = "baseline", parse(try_from_str = parse_percent))]
+    pub max_regression: Option<f64>,
+
+    /// Single request timeout, expired requests are counted as timeout errors
+    #[structopt(long, default_value = "30s", parse(try_from_str = parse_duration))]
+    pub request_timeout: Duration,
+
+    /// Time to wait for requests in flight after Ctrl+C or SIGTERM before printing partial report
+    #[structopt(long, default_value = "5s", parse(try_from_str = parse_duration))]
+    pub shutdown_timeout: Duration,
+
+    /// Live progress report interval, 0 disables it.
+    /// Progress line is printed in terminal, log records are written otherwise
+    #[structopt(long, default_value = "1s", parse(try_from_str = parse_duration))]
+    pub progress_interval: Duration,
+
+    /// Serve Prometheus metrics at http://<address>/metrics during the run, for example 127.0.0.1:9091
+    #[structopt(long)]
+    pub metrics_listen: Option<SocketAddr>,
+
+    /// Output format: text or jsonl with one JSON object per executed request
+    #[structopt(long, default_value = "text")]
+    pub output: OutputFormat,
 
     /// Log level
     #[structopt(short, parse(from_occurrences))]
@@ -49,14 +287,113 @@ impl AppArguments {
         validate_argument!(self.verbose < 3, "Verbose level must be in range [0; 2]");
 
         validate_argument!(
-            self.requests_parallel_threads > 0,
+            !matches!(self.requests_parallel_threads, Some(0)),
             "Requests threads cannot be zero"
         );
         validate_argument!(
-            self.requests_per_thread > 0,
+            !matches!(self.requests_per_thread, Some(0)),
             "Requests per thread count cannot be zero"
         );
+        validate_argument!(
+            !matches!(self.requests, Some(0)),
+            "Requests count cannot be zero"
+        );
+        validate_argument!(
+            !matches!(self.duration, Some(duration) if duration.is_zero()),
+            "Duration cannot be zero"
+        );
+        validate_argument!(self.max_in_flight > 0, "Max in flight cannot be zero");
+        validate_argument!(
+            !self.request_timeout.is_zero(),
+            "Request timeout cannot be zero"
+        );
+
+        // Профиль сам задает и модель нагрузки, и ее величину
+        if self.stages.is_some() {
+            validate_argument!(
+                self.rate.is_none()
+                    && self.duration.is_none()
+                    && self.requests.is_none()
+                    && self.requests_parallel_threads.is_none()
+                    && self.requests_per_thread.is_none(),
+                "Stages cannot be combined with rate, duration, threads or requests count"
+            );
+        } else if self.rate.is_some() {
+            validate_argument!(
+                self.requests.is_some() != self.duration.is_some(),
+                "Rate mode requires either requests count or duration"
+            );
+            validate_argument!(
+                self.requests_parallel_threads.is_none() && self.requests_per_thread.is_none(),
+                "Threads are not used in rate mode"
+            );
+        } else {
+            validate_argument!(
+                self.requests_parallel_threads.is_some(),
+                "Requests threads count is required"
+            );
+            validate_argument!(
+                self.requests_per_thread.is_some() != self.duration.is_some(),
+                "Either requests per thread count or duration is required"
+            );
+            validate_argument!(
+                self.requests.is_none(),
+                "Total requests count is used only in rate mode"
+            );
+        }
+
+        if let Some(max_error_rate) = self.max_error_rate {
+            validate_argument!(
+                (0.0..=100.0).contains(&max_error_rate),
+                "Max error rate must be in range [0; 100] percent"
+            );
+        }
 
         Ok(())
     }
 }
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("1.5s").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_duration("7").unwrap(), Duration::from_secs(7));
+        assert_eq!(parse_duration("0s").unwrap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_parse_duration_invalid() {
+        for text in ["", "-1s", "abc", "10d", "s", "NaNs", "infs"] {
+            assert!(parse_duration(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate_per_second("200/s").unwrap(), 200.0);
+        assert_eq!(parse_rate_per_second("6000/m").unwrap(), 100.0);
+        assert_eq!(parse_rate_per_second("50").unwrap(), 50.0);
+        assert_eq!(parse_rate_per_second("0/s").unwrap(), 0.0);
+        for text in ["-1/s", "10/h", "abc/s", ""] {
+            assert!(parse_rate_per_second(text).is_err(), "{text}");
+        }
+        assert!("0/s".parse::<Rate>().is_err());
+    }
+
+    #[test]
+    fn test_parse_percent() {
+        assert_eq!(parse_percent("5%").unwrap(), 5.0);
+        assert_eq!(parse_percent("2.5").unwrap(), 2.5);
+        assert!(parse_percent("-1%").is_err());
+        assert!(parse_percent("%").is_err());
+    }
+}
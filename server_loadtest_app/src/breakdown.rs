@@ -0,0 +1,203 @@
+use crate::stats::RunStats;
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+use validate_lib::Config;
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Уровень строки разбивки
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakdownLevel {
+    Project,
+    Platform,
+    Test,
+}
+
+impl BreakdownLevel {
+    pub fn name(self) -> &'static str {
+        match self {
+            BreakdownLevel::Project => "project",
+            BreakdownLevel::Platform => "platform",
+            BreakdownLevel::Test => "test",
+        }
+    }
+}
+
+/// Строка разбивки результатов: проект, платформа проекта или тест платформы
+#[derive(Debug)]
+pub struct BreakdownRow {
+    pub level: BreakdownLevel,
+    pub project: String,
+    pub platform: String,
+    /// Имя теста или product_id, если имени нет
+    pub test: String,
+    pub stats: RunStats,
+}
+
+/// Статистика по платформам и тестам проекта
+type ProjectTree = BTreeMap<String, BTreeMap<String, RunStats>>;
+
+/// Собираем статистику тестов по проектам, платформам и тестам.
+/// Конфиги с одинаковым именем проекта и тесты с одинаковым именем объединяются.
+pub fn build_breakdown(
+    configs: &[Config],
+    tests: &BTreeMap<(usize, usize), RunStats>,
+) -> Result<Vec<BreakdownRow>, eyre::Error> {
+    let mut projects: BTreeMap<String, ProjectTree> = BTreeMap::new();
+    for ((config_index, test_index), stats) in tests.iter() {
+        let config = &configs[*config_index];
+        let test = &config.tests[*test_index];
+        let label = test
+            .name
+            .clone()
+            .unwrap_or_else(|| test.purchase.product_id.clone());
+        projects
+            .entry(config.project.name.clone())
+            .or_default()
+            .entry(test.purchase.platform.clone())
+            .or_default()
+            .entry(label)
+            .or_default()
+            .merge(stats)?;
+    }
+
+    let mut rows = Vec::new();
+    for (project, platforms) in projects {
+        let project_index = rows.len();
+        let mut project_stats = RunStats::default();
+        rows.push(BreakdownRow {
+            level: BreakdownLevel::Project,
+            project: project.clone(),
+            platform: String::new(),
+            test: String::new(),
+            stats: RunStats::default(),
+        });
+
+        for (platform, tests) in platforms {
+            let platform_index = rows.len();
+            let mut platform_stats = RunStats::default();
+            rows.push(BreakdownRow {
+                level: BreakdownLevel::Platform,
+                project: project.clone(),
+                platform: platform.clone(),
+                test: String::new(),
+                stats: RunStats::default(),
+            });
+
+            for (test, stats) in tests {
+                platform_stats.merge(&stats)?;
+                rows.push(BreakdownRow {
+                    level: BreakdownLevel::Test,
+                    project: project.clone(),
+                    platform: platform.clone(),
+                    test,
+                    stats,
+                });
+            }
+
+            project_stats.merge(&platform_stats)?;
+            rows[platform_index].stats = platform_stats;
+        }
+
+        rows[project_index].stats = project_stats;
+    }
+
+    Ok(rows)
+}
+
+/// Выводим разбивку таблицей с отступами по уровням
+pub fn print_breakdown(
+    out: &mut impl Write,
+    rows: &[BreakdownRow],
+    wall_time: Duration,
+) -> std::io::Result<()> {
+    writeln!(out, "Breakdown, mSec:")?;
+    writeln!(
+        out,
+        "  {:<40}{:>10}{:>10}{:>10}{:>10}{:>10}{:>10}  errors",
+        "name", "requests", "req/s", "p50", "p90", "p99", "max"
+    )?;
+    for row in rows {
+        let name = match row.level {
+            BreakdownLevel::Project => row.project.clone(),
+            BreakdownLevel::Platform => format!("  {}", row.platform),
+            BreakdownLevel::Test => format!("    {}", row.test),
+        };
+        let latency = &row.stats.latency;
+        let errors_count = row.stats.errors.total();
+        let errors_text = format!("{} ({:.2}%)", errors_count, row.stats.error_rate());
+        let errors_text = if errors_count == 0 {
+            errors_text.green().to_string()
+        } else {
+            errors_text.red().to_string()
+        };
+        writeln!(
+            out,
+            "  {:<40}{:>10}{:>10.1}{:>10.3}{:>10.3}{:>10.3}{:>10.3}  {}",
+            name,
+            latency.count(),
+            latency.throughput(wall_time),
+            latency.percentile_millis(50.0),
+            latency.percentile_millis(90.0),
+            latency.percentile_millis(99.0),
+            latency.max_millis(),
+            errors_text
+        )?;
+    }
+    Ok(())
+}
+
+/// Поле CSV, при необходимости в кавычках
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+/// Сохраняем разбивку в CSV для последующей обработки
+pub fn write_breakdown_csv(
+    path: &Path,
+    rows: &[BreakdownRow],
+    wall_time: Duration,
+) -> Result<(), eyre::Error> {
+    let file = File::create(path)
+        .wrap_err_with(|| format!("Breakdown file {} create failed", path.display()))?;
+    let mut out = BufWriter::new(file);
+
+    writeln!(
+        out,
+        "level,project,platform,test,requests,throughput,mean_ms,p50_ms,p90_ms,p95_ms,p99_ms,max_ms,errors,error_rate"
+    )?;
+    for row in rows {
+        let latency = &row.stats.latency;
+        writeln!(
+            out,
+            "{},{},{},{},{},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{},{:.4}",
+            row.level.name(),
+            csv_field(&row.project),
+            csv_field(&row.platform),
+            csv_field(&row.test),
+            latency.count(),
+            latency.throughput(wall_time),
+            latency.mean_millis(),
+            latency.percentile_millis(50.0),
+            latency.percentile_millis(90.0),
+            latency.percentile_millis(95.0),
+            latency.percentile_millis(99.0),
+            latency.max_millis(),
+            row.stats.errors.total(),
+            row.stats.error_rate()
+        )?;
+    }
+    out.flush()
+        .wrap_err_with(|| format!("Breakdown file {} write failed", path.display()))
+}
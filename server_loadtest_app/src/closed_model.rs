@@ -0,0 +1,119 @@
+use crate::{
+    mix::TrafficMix,
+    profile::RunLimit,
+    progress::Progress,
+    request::execute_request,
+    shutdown::Shutdown,
+    stats::{LoadResults, RequestOutcome, StatsCollector},
+};
+use eyre::WrapErr;
+use reqwest::Client;
+use slog::Logger;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use validate_lib::OutputFormat;
+
+/// Пауза неактивной по профилю задачи перед следующей проверкой
+const IDLE_PAUSE: Duration = Duration::from_millis(10);
+
+/// Закрытая модель нагрузки: каждая задача отправляет следующий запрос сразу после ответа на предыдущий.
+/// По профилю меняется количество активных задач.
+pub async fn run_closed_model(
+    logger: &Logger,
+    http_client: &Client,
+    progress: Arc<Progress>,
+    shutdown: &Shutdown,
+    mix: Arc<TrafficMix>,
+    limit: &RunLimit,
+    output: OutputFormat,
+) -> Result<LoadResults, eyre::Error> {
+    let threads = match limit {
+        RunLimit::Requests { target, .. } => *target as usize,
+        RunLimit::Profile(profile) => profile.max_target().ceil() as usize,
+    };
+    let begin_time = Instant::now();
+    let collector = StatsCollector::spawn(limit.stages_count(), begin_time);
+
+    let mut executors = Vec::with_capacity(threads);
+    for thread_index in 0..threads {
+        let logger = logger.clone();
+        let mix = mix.clone();
+        let http_client = http_client.clone();
+        let progress = progress.clone();
+        let limit = limit.clone();
+        let mut shutdown = shutdown.clone();
+        let sender = collector.sender();
+
+        let join = tokio::spawn(async move {
+            let mut total_finished_requests = 0;
+
+            loop {
+                // После запроса остановки новые запросы не отправляем
+                if shutdown.is_requested() {
+                    break;
+                }
+
+                let stage = match &limit {
+                    RunLimit::Requests { count, .. } => {
+                        if total_finished_requests >= *count {
+                            break;
+                        }
+                        0
+                    }
+                    RunLimit::Profile(profile) => match profile.position(begin_time.elapsed()) {
+                        None => break,
+                        Some(position) if thread_index as f64 >= position.target => {
+                            tokio::select! {
+                                _ = tokio::time::sleep(IDLE_PAUSE) => {},
+                                _ = shutdown.requested() => {},
+                            }
+                            continue;
+                        }
+                        Some(position) => position.stage,
+                    },
+                };
+
+                let (config_index, test_index) = mix.next();
+
+                let request = execute_request(
+                    &logger,
+                    &http_client,
+                    &progress,
+                    &mix.configs()[config_index],
+                    test_index,
+                    output,
+                    Instant::now(),
+                );
+
+                // Не дождавшиеся ответа к крайнему сроку остановки запросы не учитываем
+                let (result, time_spent) = tokio::select! {
+                    result = request => result,
+                    _ = shutdown.grace_expired() => break,
+                };
+
+                // Ошибки под нагрузкой тоже результат, продолжаем работу
+                sender
+                    .send(RequestOutcome {
+                        stage,
+                        config_index,
+                        test_index,
+                        result,
+                        latency: time_spent,
+                    })
+                    .ok();
+
+                total_finished_requests += 1;
+            }
+        });
+
+        executors.push(join);
+    }
+
+    for join in executors.into_iter() {
+        join.await.wrap_err("Request spawn join failed")?;
+    }
+
+    collector.finish().await
+}
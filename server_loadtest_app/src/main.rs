@@ -1,12 +1,40 @@
 mod app_arguments;
+mod breakdown;
+mod closed_model;
+mod metrics;
+mod mix;
+mod open_model;
+mod profile;
+mod progress;
+mod report;
+mod request;
+mod shutdown;
+mod stats;
+mod timeseries;
 
-use crate::app_arguments::AppArguments;
-use eyre::{ContextCompat, WrapErr};
+use crate::{
+    app_arguments::AppArguments,
+    breakdown::{build_breakdown, print_breakdown, write_breakdown_csv},
+    closed_model::run_closed_model,
+    metrics::serve_metrics,
+    mix::TrafficMix,
+    open_model::run_open_model,
+    profile::{LoadProfile, RunLimit, TargetKind},
+    progress::{Progress, ProgressReporter},
+    report::{compare_with_baseline, load_baseline, LoadReport},
+    shutdown::Shutdown,
+    timeseries::write_timeseries_csv,
+};
+use eyre::WrapErr;
 use owo_colors::OwoColorize;
-use slog::{crit, debug, Drain, Level, Logger};
+use slog::{debug, Drain, Level, Logger};
 use slog_async::OverflowStrategy;
-use std::sync::Arc;
-use validate_lib::{check_purchase, Config};
+use std::{
+    io::Write,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use validate_lib::{Config, OutputFormat};
 
 //////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
@@ -24,7 +52,11 @@ fn init_logs(app_arguments: &AppArguments) -> Logger {
         _ => panic!("Invalid verbose level"),
     };
 
-    let term_decor = slog_term::TermDecorator::new().stdout().build();
+    // В режиме JSON Lines stdout занят записями о запросах
+    let term_decor = match app_arguments.output {
+        OutputFormat::Text => slog_term::TermDecorator::new().stdout().build(),
+        OutputFormat::Jsonl => slog_term::TermDecorator::new().stderr().build(),
+    };
     let term_drain = slog_term::FullFormat::new(term_decor)
         .use_file_location()
         .build()
@@ -78,81 +110,239 @@ async fn main() -> Result<(), eyre::Error> {
         Arc::new(configs)
     };
 
+    // Смесь запросов по весам конфигов, платформ и тестов
+    let mix = Arc::new(
+        TrafficMix::new(
+            configs,
+            app_arguments.platform_weights.as_ref(),
+            app_arguments.selection,
+        )
+        .wrap_err("Traffic mix setup failed")?,
+    );
+    mix.log_shares(&logger);
+
     // Создаем переиспользуемый HTTP клиент
     let http_client = reqwest::ClientBuilder::new()
         .tcp_keepalive(None)
+        .timeout(app_arguments.request_timeout)
         .build()
         .wrap_err("HTTP clien build failed")?;
 
-    struct ThreadResult {
-        total_finished_requests: u64,
-        total_requests_duration: std::time::Duration,
+    // Открытая модель по расписанию или закрытая с фиксированным числом задач,
+    // нагрузка постоянная или по профилю
+    let (open_model, limit) = match (&app_arguments.stages, app_arguments.rate) {
+        (Some(profile), _) => (
+            profile.kind == TargetKind::Rate,
+            RunLimit::Profile(profile.clone()),
+        ),
+        (None, Some(rate)) => match app_arguments.duration {
+            Some(duration) => (
+                true,
+                RunLimit::Profile(LoadProfile::constant(
+                    TargetKind::Rate,
+                    duration,
+                    rate.per_second,
+                )),
+            ),
+            None => (
+                true,
+                RunLimit::Requests {
+                    count: app_arguments.requests.unwrap_or_default(),
+                    target: rate.per_second,
+                },
+            ),
+        },
+        (None, None) => {
+            let threads = app_arguments.requests_parallel_threads.unwrap_or(1) as f64;
+            match app_arguments.duration {
+                Some(duration) => (
+                    false,
+                    RunLimit::Profile(LoadProfile::constant(
+                        TargetKind::Threads,
+                        duration,
+                        threads,
+                    )),
+                ),
+                None => (
+                    false,
+                    RunLimit::Requests {
+                        count: app_arguments.requests_per_thread.unwrap_or(1),
+                        target: threads,
+                    },
+                ),
+            }
+        }
+    };
+
+    // По сигналу прекращаем нагрузку и выводим отчет по уже собранным данным
+    let shutdown = Shutdown::listen(&logger, app_arguments.shutdown_timeout)?;
+
+    // Базовый прогон загружаем заранее, чтобы не узнать об ошибке в пути после долгой нагрузки
+    // Допустимое ухудшение при сравнении задается обязательно вместе с базовым прогоном
+    let baseline = match (&app_arguments.baseline, app_arguments.max_regression) {
+        (Some(path), Some(max_regression)) => Some((load_baseline(path)?, max_regression)),
+        _ => None,
+    };
+
+    let started_at = chrono::Local::now();
+    let begin_time = Instant::now();
+
+    // Прогресс во время работы, чтобы долгие прогоны можно было наблюдать и прервать
+    let progress = match app_arguments.metrics_listen {
+        Some(address) => {
+            let progress = Arc::new(Progress::with_metrics());
+            serve_metrics(&logger, address, progress.clone()).await?;
+            progress
+        }
+        None => Arc::new(Progress::default()),
+    };
+    let reporter = if app_arguments.progress_interval.is_zero() {
+        None
+    } else {
+        Some(ProgressReporter::spawn(
+            &logger,
+            progress.clone(),
+            app_arguments.progress_interval,
+        ))
+    };
+
+    let (results, schedule_stats) = if open_model {
+        let (results, schedule_stats) = run_open_model(
+            &logger,
+            &http_client,
+            progress,
+            &shutdown,
+            mix.clone(),
+            &limit,
+            app_arguments.arrivals,
+            app_arguments.max_in_flight,
+            app_arguments.output,
+        )
+        .await?;
+        (results, Some(schedule_stats))
+    } else {
+        let results = run_closed_model(
+            &logger,
+            &http_client,
+            progress,
+            &shutdown,
+            mix.clone(),
+            &limit,
+            app_arguments.output,
+        )
+        .await?;
+        (results, None)
+    };
+
+    let wall_time = begin_time.elapsed();
+
+    if let Some(reporter) = reporter {
+        reporter.stop();
     }
 
-    let mut executors = Vec::new();
-    executors.reserve(app_arguments.requests_parallel_threads as usize);
-    for _ in 0..app_arguments.requests_parallel_threads {
-        let logger = logger.clone();
-        let configs = configs.clone();
-        let http_client = http_client.clone();
-        let requests_per_thread = app_arguments.requests_per_thread;
-
-        let join = tokio::spawn(async move {
-            let mut total_finished_requests = 0;
-            let mut total_requests_duration = std::time::Duration::ZERO;
-
-            while total_finished_requests < requests_per_thread {
-                // Идем по каждому конфигу
-                for config in configs.iter() {
-                    // Идем по каждому тесту
-                    for test in config.tests.iter() {
-                        // Создаем логирование для данной задачи с контекстом
-                        let logger =
-                            logger.new(slog::o!("product" => test.purchase.product_id.clone()));
-
-                        let begin_time = std::time::Instant::now();
-
-                        check_purchase(&logger, &http_client, &config.project, test)
-                            .await
-                            .wrap_err("Request failed")?;
-
-                        let time_spent = std::time::Instant::now().duration_since(begin_time);
-
-                        total_requests_duration = total_requests_duration
-                            .checked_add(time_spent)
-                            .wrap_err("Duration overflow")?;
-
-                        total_finished_requests += 1;
-                    }
-                }
-            }
+    // Общая статистика по всем этапам
+    let run_stats = results.total()?;
 
-            Result::<ThreadResult, eyre::Error>::Ok(ThreadResult {
-                total_finished_requests,
-                total_requests_duration,
-            })
-        });
+    // Разбивка по проектам, платформам и тестам, чтобы проблемы одного не терялись в общем результате
+    let breakdown = build_breakdown(mix.configs(), &results.tests)?;
 
-        executors.push(join);
+    // В режиме JSON Lines stdout занят записями о запросах
+    let mut out: Box<dyn std::io::Write> = match app_arguments.output {
+        OutputFormat::Text => Box::new(std::io::stdout()),
+        OutputFormat::Jsonl => Box::new(std::io::stderr()),
+    };
+    if shutdown.is_requested() {
+        writeln!(
+            out,
+            "{}",
+            "Load test was interrupted, results are partial".yellow()
+        )
+        .wrap_err("Stats print failed")?;
+    }
+    if let Some(schedule_stats) = &schedule_stats {
+        schedule_stats
+            .print(&mut out, app_arguments.arrivals)
+            .wrap_err("Stats print failed")?;
     }
+    run_stats
+        .print(&mut out, wall_time)
+        .wrap_err("Stats print failed")?;
 
-    let mut total_finished_requests = 0;
-    let mut total_requests_duration = std::time::Duration::ZERO;
-    for join in executors.into_iter() {
-        match join.await.wrap_err("Request spawn join failed")? {
-            Ok(thread_stats) => {
-                total_finished_requests += thread_stats.total_finished_requests;
-                total_requests_duration += thread_stats.total_requests_duration;
-            }
-            Err(err) => {
-                crit!(logger, "Request execution failed");
-                return Err(err);
+    // Для профиля из нескольких этапов показываем каждый отдельно
+    let stages = match &limit {
+        RunLimit::Profile(profile) if profile.stages.len() > 1 => {
+            let mut stages = Vec::with_capacity(profile.stages.len());
+            let mut stage_begin = Duration::ZERO;
+            for (i, stage) in profile.stages.iter().enumerate() {
+                // При прерывании этап мог выполниться не полностью
+                let stage_time = wall_time.saturating_sub(stage_begin).min(stage.duration);
+                stage_begin += stage.duration;
+                stages.push((profile.stage_title(i), stage_time));
             }
+            stages
+        }
+        _ => Vec::new(),
+    };
+    if !stages.is_empty() {
+        writeln!(out, "Stages:").wrap_err("Stats print failed")?;
+        for ((title, stage_time), stats) in stages.iter().zip(results.stages.iter()) {
+            stats
+                .print_brief(&mut out, title, *stage_time)
+                .wrap_err("Stats print failed")?;
+        }
+    }
+
+    // Для одного теста разбивка повторяет общий результат
+    if results.tests.len() > 1 {
+        print_breakdown(&mut out, &breakdown, wall_time).wrap_err("Stats print failed")?;
+    }
+
+    // Файлы результатов пишем и для прерванного прогона
+    if let Some(path) = &app_arguments.breakdown_csv {
+        write_breakdown_csv(path, &breakdown, wall_time)?;
+    }
+    if let Some(path) = &app_arguments.timeseries {
+        write_timeseries_csv(path, &results.timeseries.points)?;
+    }
+    let report = LoadReport::new(
+        started_at,
+        wall_time,
+        shutdown.is_requested(),
+        &results,
+        &stages,
+        schedule_stats.as_ref(),
+        &breakdown,
+    )?;
+    if let Some(path) = &app_arguments.out {
+        report.save(path)?;
+    }
+
+    // Сравнение с прошлым прогоном для проверки релизов
+    if let Some((baseline, max_regression)) = &baseline {
+        let regressions =
+            compare_with_baseline(&mut out, baseline, &report.summary, *max_regression)?;
+        if !regressions.is_empty() {
+            return Err(eyre::eyre!(
+                "Regression against baseline: {}",
+                regressions.join(", ")
+            ));
         }
     }
 
-    let average_msec = total_requests_duration.as_millis() / total_finished_requests as u128;
-    println!("Average time per request: {} mSec", average_msec.green());
+    if let Some(max_error_rate) = app_arguments.max_error_rate {
+        let error_rate = run_stats.error_rate();
+        if error_rate > max_error_rate {
+            return Err(eyre::eyre!(
+                "Error rate {:.2}% exceeds allowed {:.2}%",
+                error_rate,
+                max_error_rate
+            ));
+        }
+    }
+
+    if shutdown.is_requested() {
+        return Err(eyre::eyre!("Load test was interrupted"));
+    }
 
     Ok(())
 }
@@ -0,0 +1,234 @@
+use crate::{progress::Progress, stats::error_class};
+use eyre::WrapErr;
+use slog::{debug, info, Logger};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+use validate_lib::PurchaseResponse;
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Границы корзин гистограммы времени запросов в секундах
+const DURATION_BUCKETS: [f64; 12] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0,
+];
+
+/// Максимальный размер запроса к endpoint, больше для GET /metrics не нужно
+const MAX_REQUEST_SIZE: usize = 8192;
+
+/// Накопительная гистограмма в формате Prometheus
+#[derive(Debug, Default)]
+struct DurationHistogram {
+    /// Количество значений не больше границы соответствующей корзины
+    buckets: [u64; DURATION_BUCKETS.len()],
+    sum: f64,
+    count: u64,
+}
+
+impl DurationHistogram {
+    fn record(&mut self, latency: Duration) {
+        let seconds = latency.as_secs_f64();
+        for (bucket, bound) in self.buckets.iter_mut().zip(DURATION_BUCKETS) {
+            if seconds <= bound {
+                *bucket += 1;
+            }
+        }
+        self.sum += seconds;
+        self.count += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct MetricsState {
+    /// Ключ - проект, платформа и результат: ok или класс ошибки
+    requests: BTreeMap<(String, String, String), u64>,
+    /// Ключ - проект и платформа
+    durations: BTreeMap<(String, String), DurationHistogram>,
+}
+
+/// Метрики нагрузки для сбора Prometheus
+#[derive(Debug, Default)]
+pub struct Metrics {
+    state: Mutex<MetricsState>,
+}
+
+/// Экранируем значение метки по формату Prometheus
+fn label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+impl Metrics {
+    pub fn record(
+        &self,
+        project: &str,
+        platform: &str,
+        result: Result<&PurchaseResponse, &eyre::Error>,
+        latency: Duration,
+    ) {
+        let outcome = match result {
+            Ok(_) => "ok".to_owned(),
+            Err(err) => error_class(err),
+        };
+        let mut state = self.state.lock().expect("Metrics lock poisoned");
+        *state
+            .requests
+            .entry((project.to_owned(), platform.to_owned(), outcome))
+            .or_default() += 1;
+        state
+            .durations
+            .entry((project.to_owned(), platform.to_owned()))
+            .or_default()
+            .record(latency);
+    }
+
+    /// Текстовый формат Prometheus
+    pub fn render(&self, in_flight: usize) -> String {
+        let state = self.state.lock().expect("Metrics lock poisoned");
+        let mut text = String::new();
+
+        // Запись в String не завершается ошибкой
+        text.push_str("# HELP loadtest_requests_total Completed load test requests by outcome.\n");
+        text.push_str("# TYPE loadtest_requests_total counter\n");
+        for ((project, platform, outcome), count) in state.requests.iter() {
+            writeln!(
+                text,
+                "loadtest_requests_total{{project=\"{}\",platform=\"{}\",outcome=\"{}\"}} {}",
+                label_value(project),
+                label_value(platform),
+                label_value(outcome),
+                count
+            )
+            .ok();
+        }
+
+        text.push_str(
+            "# HELP loadtest_request_duration_seconds Load test request duration in seconds.\n",
+        );
+        text.push_str("# TYPE loadtest_request_duration_seconds histogram\n");
+        for ((project, platform), histogram) in state.durations.iter() {
+            let labels = format!(
+                "project=\"{}\",platform=\"{}\"",
+                label_value(project),
+                label_value(platform)
+            );
+            for (bucket, bound) in histogram.buckets.iter().zip(DURATION_BUCKETS) {
+                writeln!(
+                    text,
+                    "loadtest_request_duration_seconds_bucket{{{labels},le=\"{bound}\"}} {bucket}"
+                )
+                .ok();
+            }
+            writeln!(
+                text,
+                "loadtest_request_duration_seconds_bucket{{{labels},le=\"+Inf\"}} {}",
+                histogram.count
+            )
+            .ok();
+            writeln!(
+                text,
+                "loadtest_request_duration_seconds_sum{{{labels}}} {}",
+                histogram.sum
+            )
+            .ok();
+            writeln!(
+                text,
+                "loadtest_request_duration_seconds_count{{{labels}}} {}",
+                histogram.count
+            )
+            .ok();
+        }
+
+        text.push_str(
+            "# HELP loadtest_requests_in_flight Load test requests waiting for response.\n",
+        );
+        text.push_str("# TYPE loadtest_requests_in_flight gauge\n");
+        writeln!(text, "loadtest_requests_in_flight {in_flight}").ok();
+
+        text
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Отвечаем на один запрос: метрики на GET /metrics, для остального 404
+async fn handle_connection(mut stream: TcpStream, progress: &Progress) -> std::io::Result<()> {
+    let mut request = Vec::with_capacity(1024);
+    let mut buffer = [0_u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 || request.len() + read > MAX_REQUEST_SIZE {
+            return Ok(());
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request_line = String::from_utf8_lossy(&request);
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => (
+            "200 OK",
+            "text/plain; version=0.0.4; charset=utf-8",
+            progress.render_metrics(),
+        ),
+        _ => ("404 Not Found", "text/plain", "Not found\n".to_owned()),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Запускаем локальный HTTP endpoint /metrics на время работы приложения
+pub async fn serve_metrics(
+    logger: &Logger,
+    address: SocketAddr,
+    progress: Arc<Progress>,
+) -> Result<(), eyre::Error> {
+    let listener = TcpListener::bind(address)
+        .await
+        .wrap_err_with(|| format!("Metrics endpoint bind to {address} failed"))?;
+    info!(
+        logger,
+        "Metrics are available at http://{}/metrics", address
+    );
+
+    let logger = logger.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(connection) => connection,
+                Err(err) => {
+                    debug!(logger, "Metrics connection accept failed: {}", err);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            let logger = logger.clone();
+            let progress = progress.clone();
+            tokio::spawn(async move {
+                if let Err(err) = handle_connection(stream, &progress).await {
+                    debug!(logger, "Metrics request from {} failed: {}", peer, err);
+                }
+            });
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,293 @@
+use crate::app_arguments::{PlatformWeights, Selection};
+use rand::Rng;
+use slog::{debug, Logger};
+use std::{
+    collections::BTreeMap,
+    sync::{Arc, Mutex},
+};
+use validate_lib::Config;
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Тест в смеси нагрузки с его долей от всех запросов
+#[derive(Debug, Clone)]
+struct MixEntry {
+    config_index: usize,
+    test_index: usize,
+    share: f64,
+}
+
+/// Смесь запросов нагрузки по весам конфигов, платформ и тестов.
+/// Веса вложенные: доля конфига делится между его платформами, доля платформы между ее тестами.
+#[derive(Debug)]
+pub struct TrafficMix {
+    configs: Arc<Vec<Config>>,
+    entries: Vec<MixEntry>,
+    selection: Selection,
+    /// Накопленные доли для случайного выбора
+    cumulative: Vec<f64>,
+    /// Текущие веса плавного взвешенного перебора
+    round_robin: Mutex<Vec<f64>>,
+}
+
+impl TrafficMix {
+    /// Без весов платформ все платформы конфига получают равные доли
+    pub fn new(
+        configs: Arc<Vec<Config>>,
+        platform_weights: Option<&PlatformWeights>,
+        selection: Selection,
+    ) -> Result<TrafficMix, eyre::Error> {
+        // Опечатка в имени платформы незаметно изменила бы смесь
+        for platform in platform_weights.iter().flat_map(|weights| weights.0.keys()) {
+            eyre::ensure!(
+                configs
+                    .iter()
+                    .flat_map(|config| config.tests.iter())
+                    .any(|test| &test.purchase.platform == platform),
+                "Platform {} from platform weights is not present in configs",
+                platform
+            );
+        }
+
+        // Доли внутри каждого конфига
+        let mut configs_entries = Vec::with_capacity(configs.len());
+        for (config_index, config) in configs.iter().enumerate() {
+            let mut platforms: BTreeMap<&str, Vec<(usize, f64)>> = BTreeMap::new();
+            for (test_index, test) in config.tests.iter().enumerate() {
+                platforms
+                    .entry(test.purchase.platform.as_str())
+                    .or_default()
+                    .push((test_index, test.weight));
+            }
+
+            let mut platforms_weights = Vec::with_capacity(platforms.len());
+            for (platform, tests) in platforms {
+                let platform_weight = match platform_weights {
+                    None => 1.0,
+                    Some(weights) => *weights.0.get(platform).ok_or_else(|| {
+                        eyre::eyre!(
+                            "Platform {} of project {} has no weight in platform weights",
+                            platform,
+                            config.project.name
+                        )
+                    })?,
+                };
+                let tests_weight: f64 = tests.iter().map(|(_, weight)| weight).sum();
+                if platform_weight > 0.0 && tests_weight > 0.0 {
+                    platforms_weights.push((platform_weight, tests_weight, tests));
+                }
+            }
+
+            let platforms_total: f64 = platforms_weights.iter().map(|(weight, ..)| weight).sum();
+            let mut entries = Vec::new();
+            for (platform_weight, tests_weight, tests) in platforms_weights {
+                for (test_index, weight) in tests {
+                    entries.push(MixEntry {
+                        config_index,
+                        test_index,
+                        share: platform_weight / platforms_total * weight / tests_weight,
+                    });
+                }
+            }
+
+            if config.load.weight > 0.0 && !entries.is_empty() {
+                configs_entries.push((config.load.weight, entries));
+            }
+        }
+
+        // Доли конфигов
+        let configs_total: f64 = configs_entries.iter().map(|(weight, _)| weight).sum();
+        let entries: Vec<MixEntry> = configs_entries
+            .into_iter()
+            .flat_map(|(config_weight, entries)| {
+                entries.into_iter().map(move |entry| MixEntry {
+                    share: entry.share * config_weight / configs_total,
+                    ..entry
+                })
+            })
+            .filter(|entry| entry.share > 0.0)
+            .collect();
+        eyre::ensure!(
+            !entries.is_empty(),
+            "No tests with non-zero weight in configs"
+        );
+
+        let cumulative = entries
+            .iter()
+            .scan(0.0, |sum, entry| {
+                *sum += entry.share;
+                Some(*sum)
+            })
+            .collect();
+
+        Ok(TrafficMix {
+            configs,
+            round_robin: Mutex::new(vec![0.0; entries.len()]),
+            entries,
+            selection,
+            cumulative,
+        })
+    }
+
+    /// Выводим итоговые доли тестов для отладки
+    pub fn log_shares(&self, logger: &Logger) {
+        for entry in self.entries.iter() {
+            let config = &self.configs[entry.config_index];
+            let test = &config.tests[entry.test_index];
+            debug!(
+                logger,
+                "Traffic share {:.2}%: project {}, platform {}, test {} {}",
+                entry.share * 100.0,
+                config.project.name,
+                test.purchase.platform,
+                entry.test_index + 1,
+                test.name.as_deref().unwrap_or("")
+            );
+        }
+    }
+
+    pub fn configs(&self) -> &[Config] {
+        &self.configs
+    }
+
+    /// Следующий тест для запроса: номер конфига и номер теста в нем
+    pub fn next(&self) -> (usize, usize) {
+        let index = match self.selection {
+            Selection::Random => {
+                let total = self.cumulative.last().copied().unwrap_or_default();
+                let value = rand::thread_rng().gen::<f64>() * total;
+                self.cumulative
+                    .partition_point(|sum| *sum <= value)
+                    .min(self.entries.len() - 1)
+            }
+            // Плавный взвешенный перебор: на любом отрезке запросов доли соблюдаются почти точно
+            Selection::RoundRobin => {
+                let total = self.cumulative.last().copied().unwrap_or_default();
+                let mut current = self.round_robin.lock().expect("Traffic mix lock poisoned");
+                let mut best = 0;
+                for (i, entry) in self.entries.iter().enumerate() {
+                    current[i] += entry.share;
+                    if current[i] > current[best] {
+                        best = i;
+                    }
+                }
+                current[best] -= total;
+                best
+            }
+        };
+        let entry = &self.entries[index];
+        (entry.config_index, entry.test_index)
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validate_lib::{LoadSettings, ProjectInfo, PurchaseData, ResponseData, TestCase};
+
+    /// Конфиг с тестами заданных платформ и весов
+    fn config(name: &str, tests: &[(&str, f64)]) -> Config {
+        Config {
+            project: ProjectInfo {
+                api_url: "http://127.0.0.1/validate".parse().unwrap(),
+                secret_key: "secret".to_owned(),
+                name: name.to_owned(),
+            },
+            tests: tests
+                .iter()
+                .enumerate()
+                .map(|(i, (platform, weight))| TestCase {
+                    name: None,
+                    purchase: PurchaseData {
+                        platform: (*platform).to_owned(),
+                        product_id: format!("product_{i}"),
+                        order_id: format!("order_{i}"),
+                        receipt: "receipt".to_owned(),
+                        receipt_signature: None,
+                        client_identifier: None,
+                    },
+                    response: ResponseData {
+                        status: "ok".to_owned(),
+                    },
+                    weight: *weight,
+                })
+                .collect(),
+            scenarios: Vec::new(),
+            load: LoadSettings::default(),
+        }
+    }
+
+    fn pick_counts(mix: &TrafficMix, picks: usize) -> BTreeMap<(usize, usize), usize> {
+        let mut counts = BTreeMap::new();
+        for _ in 0..picks {
+            *counts.entry(mix.next()).or_default() += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_round_robin_exact_shares() {
+        let configs = Arc::new(vec![config(
+            "project",
+            &[("google", 1.0), ("ios", 1.0), ("windows", 1.0)],
+        )]);
+        let weights: PlatformWeights = "google=70,ios=25,windows=5".parse().unwrap();
+
+        for picks in [20, 1000, 1013] {
+            let mix =
+                TrafficMix::new(configs.clone(), Some(&weights), Selection::RoundRobin).unwrap();
+            let counts = pick_counts(&mix, picks);
+            for (test_index, share) in [(0, 0.70), (1, 0.25), (2, 0.05)] {
+                let count = counts.get(&(0, test_index)).copied().unwrap_or_default() as f64;
+                let expected = share * picks as f64;
+                assert!(
+                    (count - expected).abs() <= 1.0,
+                    "test {test_index}: {count} picks of {picks}, expected {expected}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_nested_weights_shares() {
+        // Доля конфига делится между платформами, доля платформы между тестами
+        let mut heavy = config("heavy", &[("google", 3.0), ("google", 1.0), ("ios", 1.0)]);
+        heavy.load.weight = 3.0;
+        let light = config("light", &[("google", 1.0)]);
+        let mix =
+            TrafficMix::new(Arc::new(vec![heavy, light]), None, Selection::RoundRobin).unwrap();
+
+        let counts = pick_counts(&mix, 800);
+        assert_eq!(counts[&(0, 0)], 225);
+        assert_eq!(counts[&(0, 1)], 75);
+        assert_eq!(counts[&(0, 2)], 300);
+        assert_eq!(counts[&(1, 0)], 200);
+    }
+
+    #[test]
+    fn test_zero_weights_rejected() {
+        let configs = Arc::new(vec![config("project", &[("google", 0.0), ("ios", 0.0)])]);
+        assert!(TrafficMix::new(configs.clone(), None, Selection::Random).is_err());
+
+        let weights: PlatformWeights = "google=0,ios=0".parse().unwrap();
+        let configs = Arc::new(vec![config("project", &[("google", 1.0), ("ios", 1.0)])]);
+        assert!(TrafficMix::new(configs, Some(&weights), Selection::Random).is_err());
+    }
+
+    #[test]
+    fn test_random_skips_zero_weight() {
+        let configs = Arc::new(vec![config("project", &[("google", 1.0), ("ios", 0.0)])]);
+        let mix = TrafficMix::new(configs, None, Selection::Random).unwrap();
+        let counts = pick_counts(&mix, 100);
+        assert_eq!(counts.get(&(0, 0)), Some(&100));
+    }
+
+    #[test]
+    fn test_unknown_platform_weight_rejected() {
+        let configs = Arc::new(vec![config("project", &[("google", 1.0)])]);
+        let weights: PlatformWeights = "google=1,iso=1".parse().unwrap();
+        assert!(TrafficMix::new(configs, Some(&weights), Selection::RoundRobin).is_err());
+    }
+}
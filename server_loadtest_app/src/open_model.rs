@@ -0,0 +1,190 @@
+use crate::{
+    app_arguments::Arrivals,
+    mix::TrafficMix,
+    profile::RunLimit,
+    progress::Progress,
+    request::execute_request,
+    shutdown::Shutdown,
+    stats::{LoadResults, RequestOutcome, StatsCollector},
+};
+use owo_colors::OwoColorize;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use reqwest::Client;
+use slog::Logger;
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use validate_lib::OutputFormat;
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Отставание от расписания, после которого запрос считается опоздавшим
+const LATE_THRESHOLD: Duration = Duration::from_millis(10);
+
+/// Максимальный шаг по профилю, в течение которого частота считается постоянной
+const SCHEDULE_STEP: Duration = Duration::from_millis(10);
+
+/// Насколько клиент справился с расписанием открытой модели
+#[derive(Debug, Default, Clone)]
+pub struct ScheduleStats {
+    /// Запросов по расписанию
+    pub scheduled: u64,
+    /// Не отправлены из-за превышения лимита одновременных запросов
+    pub dropped: u64,
+    /// Отправлены позже запланированного времени
+    pub late: u64,
+}
+
+impl ScheduleStats {
+    pub fn print(&self, out: &mut impl Write, arrivals: Arrivals) -> std::io::Result<()> {
+        let arrivals = match arrivals {
+            Arrivals::Fixed => "fixed",
+            Arrivals::Poisson => "poisson",
+        };
+        writeln!(
+            out,
+            "Scheduled: {} ({} arrivals), dropped {}, late {}",
+            self.scheduled,
+            arrivals,
+            self.dropped.red(),
+            self.late.yellow()
+        )?;
+        writeln!(out, "Latency is measured from the scheduled send time")
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Открытая модель нагрузки: запросы отправляются по расписанию независимо от ответов.
+/// Время запроса считается от запланированного момента, чтобы задержки клиента не скрывали медленные ответы.
+/// По профилю меняется частота запросов.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_open_model(
+    logger: &Logger,
+    http_client: &Client,
+    progress: Arc<Progress>,
+    shutdown: &Shutdown,
+    mix: Arc<TrafficMix>,
+    limit: &RunLimit,
+    arrivals: Arrivals,
+    max_in_flight: usize,
+    output: OutputFormat,
+) -> Result<(LoadResults, ScheduleStats), eyre::Error> {
+    // Статистику собираем параллельно с отправкой
+    let begin_time = Instant::now();
+    let collector = StatsCollector::spawn(limit.stages_count(), begin_time);
+
+    let in_flight = Arc::new(AtomicUsize::new(0));
+    let mut schedule_stats = ScheduleStats::default();
+    let mut rng = StdRng::from_entropy();
+    let mut offset = Duration::ZERO;
+
+    // Сколько запросов накопилось по расписанию, первый отправляем сразу
+    let mut pending = 1.0_f64;
+    let mut next_arrival = 1.0_f64;
+
+    'schedule: loop {
+        // Двигаемся по профилю небольшими шагами, частота внутри шага постоянная,
+        // пока не накопится следующий запрос. Так корректно работает и плавный рост с нуля.
+        let stage = loop {
+            let (stage, rate) = match limit {
+                RunLimit::Requests { count, target } => {
+                    if schedule_stats.scheduled >= *count {
+                        break 'schedule;
+                    }
+                    (0, *target)
+                }
+                RunLimit::Profile(profile) => match profile.position(offset) {
+                    Some(position) => (position.stage, position.target),
+                    None => break 'schedule,
+                },
+            };
+            if rate <= 0.0 {
+                offset += SCHEDULE_STEP;
+                continue;
+            }
+            let remaining = next_arrival - pending;
+            if remaining <= rate * SCHEDULE_STEP.as_secs_f64() {
+                offset += Duration::from_secs_f64(remaining / rate);
+                break stage;
+            }
+            offset += SCHEDULE_STEP;
+            pending += rate * SCHEDULE_STEP.as_secs_f64();
+        };
+
+        // Интервал до следующего запроса в количестве запросов по текущей частоте
+        pending = 0.0;
+        next_arrival = match arrivals {
+            Arrivals::Fixed => 1.0,
+            Arrivals::Poisson => -(1.0 - rng.gen::<f64>()).ln(),
+        };
+
+        // Запланированное время запроса
+        let scheduled_time = begin_time + offset;
+
+        // После запроса остановки новые запросы не отправляем
+        let mut shutdown = shutdown.clone();
+        tokio::select! {
+            _ = tokio::time::sleep_until(tokio::time::Instant::from_std(scheduled_time)) => {},
+            _ = shutdown.requested() => break,
+        }
+        schedule_stats.scheduled += 1;
+
+        if Instant::now().saturating_duration_since(scheduled_time) > LATE_THRESHOLD {
+            schedule_stats.late += 1;
+        }
+
+        // Клиент не справляется, дальше копить запросы нет смысла
+        if in_flight.load(Ordering::Acquire) >= max_in_flight {
+            schedule_stats.dropped += 1;
+            continue;
+        }
+        in_flight.fetch_add(1, Ordering::AcqRel);
+
+        let logger = logger.clone();
+        let mix = mix.clone();
+        let http_client = http_client.clone();
+        let progress = progress.clone();
+        let in_flight = in_flight.clone();
+        let sender = collector.sender();
+        tokio::spawn(async move {
+            let (config_index, test_index) = mix.next();
+            let request = execute_request(
+                &logger,
+                &http_client,
+                &progress,
+                &mix.configs()[config_index],
+                test_index,
+                output,
+                scheduled_time,
+            );
+
+            // Не дождавшиеся ответа к крайнему сроку остановки запросы не учитываем
+            let (result, latency) = tokio::select! {
+                result = request => result,
+                _ = shutdown.grace_expired() => return,
+            };
+            in_flight.fetch_sub(1, Ordering::AcqRel);
+            // Получатель живет до завершения всех отправителей
+            sender
+                .send(RequestOutcome {
+                    stage,
+                    config_index,
+                    test_index,
+                    result,
+                    latency,
+                })
+                .ok();
+        });
+    }
+
+    // Ждем завершения оставшихся запросов
+    let results = collector.finish().await?;
+
+    Ok((results, schedule_stats))
+}
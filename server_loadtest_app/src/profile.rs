@@ -0,0 +1,255 @@
+use crate::app_arguments::{parse_duration, parse_rate_per_second};
+use std::{str::FromStr, time::Duration};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Что задает величина нагрузки в профиле
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetKind {
+    /// Количество одновременно работающих задач закрытой модели
+    Threads,
+    /// Запросов в секунду для открытой модели
+    Rate,
+}
+
+/// Этап профиля: за время этапа нагрузка линейно меняется до целевой
+#[derive(Debug, Clone)]
+pub struct Stage {
+    pub duration: Duration,
+    pub target: f64,
+}
+
+/// Профиль нагрузки по времени
+#[derive(Debug, Clone)]
+pub struct LoadProfile {
+    pub kind: TargetKind,
+    /// Нагрузка в начале первого этапа
+    pub start_target: f64,
+    pub stages: Vec<Stage>,
+}
+
+/// Текущее положение в профиле
+#[derive(Debug, Clone, Copy)]
+pub struct ProfilePosition {
+    pub stage: usize,
+    pub target: f64,
+}
+
+/// Длительность в коротком виде для вывода
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs_f64();
+    if seconds >= 3600.0 && seconds % 3600.0 == 0.0 {
+        format!("{}h", seconds / 3600.0)
+    } else if seconds >= 60.0 && seconds % 60.0 == 0.0 {
+        format!("{}m", seconds / 60.0)
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+impl LoadProfile {
+    /// Постоянная нагрузка в течение заданного времени
+    pub fn constant(kind: TargetKind, duration: Duration, target: f64) -> LoadProfile {
+        LoadProfile {
+            kind,
+            start_target: target,
+            stages: vec![Stage { duration, target }],
+        }
+    }
+
+    pub fn max_target(&self) -> f64 {
+        self.stages
+            .iter()
+            .map(|stage| stage.target)
+            .fold(self.start_target, f64::max)
+    }
+
+    /// Этап и нагрузка в заданный момент от начала, None после окончания профиля
+    pub fn position(&self, elapsed: Duration) -> Option<ProfilePosition> {
+        let mut stage_begin = Duration::ZERO;
+        let mut from_target = self.start_target;
+        for (i, stage) in self.stages.iter().enumerate() {
+            let stage_end = stage_begin + stage.duration;
+            if elapsed < stage_end {
+                let progress = (elapsed - stage_begin).as_secs_f64() / stage.duration.as_secs_f64();
+                return Some(ProfilePosition {
+                    stage: i,
+                    target: from_target + (stage.target - from_target) * progress,
+                });
+            }
+            stage_begin = stage_end;
+            from_target = stage.target;
+        }
+        None
+    }
+
+    /// Описание этапа для отчета
+    pub fn stage_title(&self, index: usize) -> String {
+        let stage = &self.stages[index];
+        let from_target = match index {
+            0 => self.start_target,
+            _ => self.stages[index - 1].target,
+        };
+        let target = match self.kind {
+            TargetKind::Threads => format!("{} threads", stage.target),
+            TargetKind::Rate => format!("{} req/s", stage.target),
+        };
+        let change = if from_target == stage.target {
+            "at"
+        } else {
+            "ramp to"
+        };
+        format!(
+            "Stage {} ({} {} {})",
+            index + 1,
+            format_duration(stage.duration),
+            change,
+            target
+        )
+    }
+}
+
+impl FromStr for LoadProfile {
+    type Err = eyre::Error;
+
+    /// Формат: "30s:10,2m:50,30s:0" для задач или "1m:100/s,1m:500/s" для частоты запросов
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut kind = None;
+        let mut stages = Vec::new();
+        for stage_text in s.split(',') {
+            let (duration, target) = stage_text
+                .split_once(':')
+                .ok_or_else(|| eyre::eyre!("Stage {} must be duration:target", stage_text))?;
+
+            let duration = parse_duration(duration)?;
+            if duration.is_zero() {
+                return Err(eyre::eyre!("Stage {} duration cannot be zero", stage_text));
+            }
+
+            let (stage_kind, target) = if target.contains('/') {
+                (TargetKind::Rate, parse_rate_per_second(target)?)
+            } else {
+                let threads: u32 = target
+                    .trim()
+                    .parse()
+                    .map_err(|_| eyre::eyre!("Invalid stage threads count {}", target))?;
+                (TargetKind::Threads, threads as f64)
+            };
+            if kind.get_or_insert(stage_kind) != &stage_kind {
+                return Err(eyre::eyre!(
+                    "Stages cannot mix threads count and requests rate"
+                ));
+            }
+
+            stages.push(Stage { duration, target });
+        }
+
+        Ok(LoadProfile {
+            kind: kind.ok_or_else(|| eyre::eyre!("Stages cannot be empty"))?,
+            start_target: 0.0,
+            stages,
+        })
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Когда завершать нагрузку
+#[derive(Debug, Clone)]
+pub enum RunLimit {
+    /// Постоянная нагрузка до заданного количества запросов:
+    /// на задачу для закрытой модели, всего для открытой
+    Requests { count: u64, target: f64 },
+    /// Нагрузка меняется по профилю до его окончания
+    Profile(LoadProfile),
+}
+
+impl RunLimit {
+    pub fn stages_count(&self) -> usize {
+        match self {
+            RunLimit::Requests { .. } => 1,
+            RunLimit::Profile(profile) => profile.stages.len(),
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_threads_profile() {
+        let profile: LoadProfile = "30s:10,2m:50,500ms:0".parse().unwrap();
+        assert_eq!(profile.kind, TargetKind::Threads);
+        assert_eq!(profile.start_target, 0.0);
+        let stages: Vec<(Duration, f64)> = profile
+            .stages
+            .iter()
+            .map(|stage| (stage.duration, stage.target))
+            .collect();
+        assert_eq!(
+            stages,
+            [
+                (Duration::from_secs(30), 10.0),
+                (Duration::from_secs(120), 50.0),
+                (Duration::from_millis(500), 0.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_rate_profile() {
+        let profile: LoadProfile = "1m:100/s,1h:600/m".parse().unwrap();
+        assert_eq!(profile.kind, TargetKind::Rate);
+        assert_eq!(profile.stages[0].target, 100.0);
+        assert_eq!(profile.stages[1].duration, Duration::from_secs(3600));
+        assert_eq!(profile.stages[1].target, 10.0);
+    }
+
+    #[test]
+    fn test_parse_invalid_profile() {
+        for text in [
+            "",
+            "30s",
+            "0s:10",
+            "-5s:10",
+            "30s:-1",
+            "30s:-5/s",
+            "30s:10,30s:100/s",
+            "30s:100/s,30s:10",
+            "30s:10/h",
+        ] {
+            assert!(text.parse::<LoadProfile>().is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn test_position_interpolation() {
+        let profile: LoadProfile = "10s:100,10s:100,10s:0".parse().unwrap();
+        let target_at = |seconds: f64| {
+            profile
+                .position(Duration::from_secs_f64(seconds))
+                .map(|position| (position.stage, position.target))
+        };
+
+        assert_eq!(target_at(0.0), Some((0, 0.0)));
+        assert_eq!(target_at(5.0), Some((0, 50.0)));
+        // Граница этапа относится к следующему этапу
+        assert_eq!(target_at(10.0), Some((1, 100.0)));
+        assert_eq!(target_at(15.0), Some((1, 100.0)));
+        assert_eq!(target_at(20.0), Some((2, 100.0)));
+        assert_eq!(target_at(25.0), Some((2, 50.0)));
+        assert_eq!(target_at(30.0), None);
+    }
+
+    #[test]
+    fn test_constant_profile() {
+        let profile = LoadProfile::constant(TargetKind::Rate, Duration::from_secs(10), 20.0);
+        assert_eq!(profile.max_target(), 20.0);
+        let position = profile.position(Duration::from_secs(9)).unwrap();
+        assert_eq!(position.target, 20.0);
+        assert!(profile.position(Duration::from_secs(10)).is_none());
+    }
+}
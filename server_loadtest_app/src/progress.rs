@@ -0,0 +1,143 @@
+use crate::{metrics::Metrics, stats::RunStats};
+use slog::{info, Logger};
+use std::{
+    io::{IsTerminal, Write},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::task::JoinHandle;
+use validate_lib::PurchaseResponse;
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Текущее состояние нагрузки для периодического отчета во время работы
+#[derive(Debug, Default)]
+pub struct Progress {
+    /// Выполняющиеся сейчас запросы
+    active: AtomicUsize,
+    /// Всего завершено запросов
+    total_requests: AtomicU64,
+    /// Всего запросов с ошибкой
+    total_errors: AtomicU64,
+    /// Статистика с момента прошлого отчета
+    interval: Mutex<RunStats>,
+    /// Метрики для Prometheus, если включен endpoint
+    metrics: Option<Metrics>,
+}
+
+impl Progress {
+    pub fn with_metrics() -> Progress {
+        Progress {
+            metrics: Some(Metrics::default()),
+            ..Default::default()
+        }
+    }
+
+    pub fn request_started(&self) {
+        self.active.fetch_add(1, Ordering::AcqRel);
+    }
+
+    pub fn request_finished(
+        &self,
+        project: &str,
+        platform: &str,
+        result: Result<&PurchaseResponse, &eyre::Error>,
+        latency: Duration,
+    ) {
+        self.active.fetch_sub(1, Ordering::AcqRel);
+        self.total_requests.fetch_add(1, Ordering::AcqRel);
+        if result.is_err() {
+            self.total_errors.fetch_add(1, Ordering::AcqRel);
+        }
+        self.interval
+            .lock()
+            .expect("Progress lock poisoned")
+            .record(result, latency);
+        if let Some(metrics) = &self.metrics {
+            metrics.record(project, platform, result, latency);
+        }
+    }
+
+    /// Метрики в формате Prometheus вместе с количеством выполняющихся запросов
+    pub fn render_metrics(&self) -> String {
+        match &self.metrics {
+            Some(metrics) => metrics.render(self.active.load(Ordering::Acquire)),
+            None => String::new(),
+        }
+    }
+
+    /// Забираем статистику интервала, начиная новый
+    fn take_interval(&self) -> RunStats {
+        std::mem::take(&mut *self.interval.lock().expect("Progress lock poisoned"))
+    }
+
+    /// Строка отчета за интервал
+    fn interval_line(&self, elapsed: Duration, interval: Duration) -> String {
+        let stats = self.take_interval();
+        format!(
+            "[{:>7.1}s] requests {}, {:.1} req/s, p50 {:.3} mSec, p99 {:.3} mSec, errors {}, active {}",
+            elapsed.as_secs_f64(),
+            self.total_requests.load(Ordering::Acquire),
+            stats.latency.count() as f64 / interval.as_secs_f64(),
+            stats.latency.percentile_millis(50.0),
+            stats.latency.percentile_millis(99.0),
+            self.total_errors.load(Ordering::Acquire),
+            self.active.load(Ordering::Acquire)
+        )
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Задача периодического вывода прогресса
+pub struct ProgressReporter {
+    join: JoinHandle<()>,
+    /// Строка прогресса в терминале, которую надо стереть перед итоговым отчетом
+    terminal: bool,
+}
+
+impl ProgressReporter {
+    /// Запускаем вывод раз в заданный интервал: строкой прогресса в терминале,
+    /// записями в лог если вывод перенаправлен
+    pub fn spawn(logger: &Logger, progress: Arc<Progress>, period: Duration) -> ProgressReporter {
+        let terminal = std::io::stderr().is_terminal();
+        let logger = logger.clone();
+        let begin_time = Instant::now();
+
+        let join = tokio::spawn(async move {
+            let mut timer = tokio::time::interval(period);
+            // Первое срабатывание сразу, пропускаем его
+            timer.tick().await;
+            let mut last_time = Instant::now();
+            loop {
+                timer.tick().await;
+                let now = Instant::now();
+                let line = progress.interval_line(now - begin_time, now - last_time);
+                last_time = now;
+
+                if terminal {
+                    let mut err = std::io::stderr();
+                    write!(err, "\r\x1b[2K{}", line).ok();
+                    err.flush().ok();
+                } else {
+                    info!(logger, "{}", line);
+                }
+            }
+        });
+
+        ProgressReporter { join, terminal }
+    }
+
+    /// Останавливаем вывод перед итоговым отчетом
+    pub fn stop(self) {
+        self.join.abort();
+        if self.terminal {
+            let mut err = std::io::stderr();
+            write!(err, "\r\x1b[2K").ok();
+            err.flush().ok();
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use crate::{
+    breakdown::BreakdownRow,
+    open_model::ScheduleStats,
+    stats::{LoadResults, RunStats},
+};
+use eyre::WrapErr;
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, io::Write, path::Path, time::Duration};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Распределение времени запросов в миллисекундах
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub min: f64,
+    pub mean: f64,
+    pub stddev: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub p95: f64,
+    pub p99: f64,
+    pub p99_9: f64,
+    pub max: f64,
+}
+
+/// Итоговые показатели части прогона
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsSummary {
+    pub requests: u64,
+    /// Запросов в секунду
+    pub throughput: f64,
+    pub errors: u64,
+    /// Доля запросов с ошибкой в процентах
+    pub error_rate: f64,
+    pub latency_ms: LatencySummary,
+    /// Количество ошибок по классам
+    pub error_classes: BTreeMap<String, u64>,
+}
+
+impl StatsSummary {
+    pub fn new(stats: &RunStats, wall_time: Duration) -> StatsSummary {
+        let latency = &stats.latency;
+        StatsSummary {
+            requests: latency.count(),
+            throughput: latency.throughput(wall_time),
+            errors: stats.errors.total(),
+            error_rate: stats.error_rate(),
+            latency_ms: LatencySummary {
+                min: latency.min_millis(),
+                mean: latency.mean_millis(),
+                stddev: latency.stddev_millis(),
+                p50: latency.percentile_millis(50.0),
+                p90: latency.percentile_millis(90.0),
+                p95: latency.percentile_millis(95.0),
+                p99: latency.percentile_millis(99.0),
+                p99_9: latency.percentile_millis(99.9),
+                max: latency.max_millis(),
+            },
+            error_classes: stats.errors.counts().clone(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StageReport {
+    pub title: String,
+    pub summary: StatsSummary,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduleReport {
+    pub scheduled: u64,
+    pub dropped: u64,
+    pub late: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BreakdownReport {
+    pub level: &'static str,
+    pub project: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub platform: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub test: String,
+    pub summary: StatsSummary,
+}
+
+/// Полный отчет о прогоне для сохранения в JSON
+#[derive(Debug, Serialize)]
+pub struct LoadReport {
+    pub started_at: String,
+    pub wall_time_s: f64,
+    /// Прогон прерван сигналом, результаты неполные
+    pub interrupted: bool,
+    pub summary: StatsSummary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<ScheduleReport>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub stages: Vec<StageReport>,
+    pub breakdown: Vec<BreakdownReport>,
+}
+
+impl LoadReport {
+    /// Время этапов передается отдельно, так как при прерывании они могли выполниться не полностью
+    pub fn new(
+        started_at: chrono::DateTime<chrono::Local>,
+        wall_time: Duration,
+        interrupted: bool,
+        results: &LoadResults,
+        stages: &[(String, Duration)],
+        schedule: Option<&ScheduleStats>,
+        breakdown: &[BreakdownRow],
+    ) -> Result<LoadReport, eyre::Error> {
+        Ok(LoadReport {
+            started_at: started_at.to_rfc3339(),
+            wall_time_s: wall_time.as_secs_f64(),
+            interrupted,
+            summary: StatsSummary::new(&results.total()?, wall_time),
+            schedule: schedule.map(|schedule| ScheduleReport {
+                scheduled: schedule.scheduled,
+                dropped: schedule.dropped,
+                late: schedule.late,
+            }),
+            stages: stages
+                .iter()
+                .zip(results.stages.iter())
+                .map(|((title, stage_time), stats)| StageReport {
+                    title: title.clone(),
+                    summary: StatsSummary::new(stats, *stage_time),
+                })
+                .collect(),
+            breakdown: breakdown
+                .iter()
+                .map(|row| BreakdownReport {
+                    level: row.level.name(),
+                    project: row.project.clone(),
+                    platform: row.platform.clone(),
+                    test: row.test.clone(),
+                    summary: StatsSummary::new(&row.stats, wall_time),
+                })
+                .collect(),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), eyre::Error> {
+        let text = serde_json::to_string_pretty(self).wrap_err("Report serialize failed")?;
+        std::fs::write(path, text)
+            .wrap_err_with(|| format!("Report file {} write failed", path.display()))
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Из сохраненного отчета для сравнения нужны только общие показатели
+#[derive(Debug, Deserialize)]
+struct BaselineReport {
+    summary: StatsSummary,
+}
+
+pub fn load_baseline(path: &Path) -> Result<StatsSummary, eyre::Error> {
+    let file = std::fs::File::open(path)
+        .wrap_err_with(|| format!("Baseline file {} open failed", path.display()))?;
+    let report: BaselineReport = serde_json::from_reader(std::io::BufReader::new(file))
+        .wrap_err_with(|| format!("Baseline file {} parse failed", path.display()))?;
+    Ok(report.summary)
+}
+
+/// Сравниваемый показатель
+struct Metric {
+    name: &'static str,
+    unit: &'static str,
+    baseline: f64,
+    current: f64,
+    /// Рост показателя - это ухудшение
+    higher_is_worse: bool,
+    /// Изменение считаем в процентных пунктах, а не в процентах от базового значения
+    absolute: bool,
+}
+
+impl Metric {
+    /// Ухудшение в процентах от базового значения или в процентных пунктах
+    fn regression(&self) -> f64 {
+        let change = if self.absolute {
+            self.current - self.baseline
+        } else if self.baseline == 0.0 {
+            // От нуля любое изменение относительно бесконечно, считаем только знак
+            match self.current.partial_cmp(&0.0) {
+                Some(std::cmp::Ordering::Greater) => f64::INFINITY,
+                _ => 0.0,
+            }
+        } else {
+            (self.current - self.baseline) * 100.0 / self.baseline
+        };
+        if self.higher_is_worse {
+            change
+        } else {
+            -change
+        }
+    }
+}
+
+/// Сравниваем с прошлым прогоном, возвращаем имена ухудшившихся сверх допустимого показателей.
+/// Время и пропускная способность сравниваются в процентах, доля ошибок в процентных пунктах.
+pub fn compare_with_baseline(
+    out: &mut impl Write,
+    baseline: &StatsSummary,
+    current: &StatsSummary,
+    max_regression: f64,
+) -> Result<Vec<&'static str>, eyre::Error> {
+    let metrics = [
+        Metric {
+            name: "p50",
+            unit: "mSec",
+            baseline: baseline.latency_ms.p50,
+            current: current.latency_ms.p50,
+            higher_is_worse: true,
+            absolute: false,
+        },
+        Metric {
+            name: "p99",
+            unit: "mSec",
+            baseline: baseline.latency_ms.p99,
+            current: current.latency_ms.p99,
+            higher_is_worse: true,
+            absolute: false,
+        },
+        Metric {
+            name: "throughput",
+            unit: "req/s",
+            baseline: baseline.throughput,
+            current: current.throughput,
+            higher_is_worse: false,
+            absolute: false,
+        },
+        Metric {
+            name: "error rate",
+            unit: "%",
+            baseline: baseline.error_rate,
+            current: current.error_rate,
+            higher_is_worse: true,
+            absolute: true,
+        },
+    ];
+
+    writeln!(
+        out,
+        "Baseline comparison, max regression {max_regression:.1}%:"
+    )?;
+
+    let mut regressions = Vec::new();
+    for metric in metrics.iter() {
+        let regression = metric.regression();
+        let unit = if metric.absolute { "pp" } else { "%" };
+        let change_text = if metric.absolute {
+            format!("{:+.2}{}", metric.current - metric.baseline, unit)
+        } else if metric.baseline == 0.0 {
+            "n/a".to_owned()
+        } else {
+            format!(
+                "{:+.1}{}",
+                (metric.current - metric.baseline) * 100.0 / metric.baseline,
+                unit
+            )
+        };
+
+        let verdict = if regression > max_regression {
+            regressions.push(metric.name);
+            "REGRESSION".red().to_string()
+        } else if regression > 0.0 {
+            "worse".yellow().to_string()
+        } else {
+            "ok".green().to_string()
+        };
+        writeln!(
+            out,
+            "  {:<20}{:>12.3} -> {:<12.3}{:>10}  {}",
+            format!("{}, {}", metric.name, metric.unit),
+            metric.baseline,
+            metric.current,
+            change_text,
+            verdict
+        )?;
+    }
+
+    Ok(regressions)
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(p50: f64, p99: f64, throughput: f64, error_rate: f64) -> StatsSummary {
+        StatsSummary {
+            requests: 1000,
+            throughput,
+            errors: 0,
+            error_rate,
+            latency_ms: LatencySummary {
+                min: 0.0,
+                mean: p50,
+                stddev: 0.0,
+                p50,
+                p90: p99,
+                p95: p99,
+                p99,
+                p99_9: p99,
+                max: p99,
+            },
+            error_classes: BTreeMap::new(),
+        }
+    }
+
+    fn regressions(
+        baseline: &StatsSummary,
+        current: &StatsSummary,
+        max_regression: f64,
+    ) -> Vec<&'static str> {
+        compare_with_baseline(&mut Vec::new(), baseline, current, max_regression).unwrap()
+    }
+
+    #[test]
+    fn test_latency_growth_is_regression() {
+        let baseline = summary(10.0, 50.0, 100.0, 0.0);
+        assert_eq!(
+            regressions(&baseline, &summary(12.0, 60.0, 100.0, 0.0), 10.0),
+            ["p50", "p99"]
+        );
+        assert!(regressions(&baseline, &summary(5.0, 20.0, 100.0, 0.0), 10.0).is_empty());
+    }
+
+    #[test]
+    fn test_throughput_drop_is_regression() {
+        let baseline = summary(10.0, 50.0, 100.0, 0.0);
+        assert_eq!(
+            regressions(&baseline, &summary(10.0, 50.0, 80.0, 0.0), 10.0),
+            ["throughput"]
+        );
+        assert!(regressions(&baseline, &summary(10.0, 50.0, 150.0, 0.0), 10.0).is_empty());
+    }
+
+    #[test]
+    fn test_error_rate_in_percentage_points() {
+        // 1% -> 1.5% это +50% относительно, но всего +0.5 процентного пункта
+        let baseline = summary(10.0, 50.0, 100.0, 1.0);
+        assert!(regressions(&baseline, &summary(10.0, 50.0, 100.0, 1.5), 1.0).is_empty());
+        assert_eq!(
+            regressions(&baseline, &summary(10.0, 50.0, 100.0, 2.5), 1.0),
+            ["error rate"]
+        );
+        // С нулевой базой процентные пункты считаются как обычно
+        let baseline = summary(10.0, 50.0, 100.0, 0.0);
+        assert_eq!(
+            regressions(&baseline, &summary(10.0, 50.0, 100.0, 0.5), 0.0),
+            ["error rate"]
+        );
+    }
+
+    #[test]
+    fn test_zero_baseline() {
+        let baseline = summary(0.0, 0.0, 0.0, 0.0);
+        // Рост от нуля - бесконечное ухудшение для времени и улучшение для пропускной способности
+        assert_eq!(
+            regressions(&baseline, &summary(1.0, 0.0, 100.0, 0.0), 1000.0),
+            ["p50"]
+        );
+        assert!(regressions(&baseline, &baseline, 0.0).is_empty());
+    }
+
+    #[test]
+    fn test_exact_threshold_is_allowed() {
+        let baseline = summary(100.0, 200.0, 100.0, 1.0);
+        assert!(regressions(&baseline, &summary(110.0, 220.0, 90.0, 11.0), 10.0).is_empty());
+        assert_eq!(
+            regressions(&baseline, &summary(111.0, 220.0, 90.0, 11.0), 10.0),
+            ["p50"]
+        );
+    }
+}
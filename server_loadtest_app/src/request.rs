@@ -0,0 +1,48 @@
+use crate::progress::Progress;
+use reqwest::Client;
+use slog::Logger;
+use std::time::{Duration, Instant};
+use validate_lib::{check_purchase, CheckRecord, Config, OutputFormat, PurchaseResponse};
+
+/// Выполняем запрос для теста конфига, время считаем от begin_time.
+/// В режиме JSON Lines сразу выводим запись о запросе.
+pub async fn execute_request(
+    logger: &Logger,
+    http_client: &Client,
+    progress: &Progress,
+    config: &Config,
+    test_index: usize,
+    output: OutputFormat,
+    begin_time: Instant,
+) -> (Result<PurchaseResponse, eyre::Error>, Duration) {
+    let test = &config.tests[test_index];
+
+    // Создаем логирование для данной задачи с контекстом
+    let logger = logger.new(slog::o!("product" => test.purchase.product_id.clone()));
+
+    progress.request_started();
+
+    let result = check_purchase(&logger, http_client, &config.project, test).await;
+
+    let time_spent = begin_time.elapsed();
+
+    progress.request_finished(
+        &config.project.name,
+        &test.purchase.platform,
+        result.as_ref(),
+        time_spent,
+    );
+
+    if output == OutputFormat::Jsonl {
+        let record = CheckRecord::new(
+            &config.project,
+            test_index + 1,
+            test,
+            result.as_ref(),
+            time_spent,
+        );
+        println!("{}", record.to_json_line());
+    }
+
+    (result, time_spent)
+}
@@ -0,0 +1,80 @@
+use eyre::WrapErr;
+use slog::{warn, Logger};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Запрос остановки нагрузки по сигналу.
+/// После запроса новые запросы не отправляются, выполняющиеся ждем до крайнего срока.
+#[derive(Debug, Clone)]
+pub struct Shutdown {
+    /// Крайний срок ожидания выполняющихся запросов, есть только после запроса остановки
+    deadline: watch::Receiver<Option<Instant>>,
+}
+
+impl Shutdown {
+    /// Начинаем ждать SIGINT/SIGTERM, повторный сигнал завершает приложение сразу
+    pub fn listen(logger: &Logger, grace: Duration) -> Result<Shutdown, eyre::Error> {
+        let (sender, deadline) = watch::channel(None);
+
+        #[cfg(unix)]
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+                .wrap_err("SIGTERM handler setup failed")?;
+
+        let logger = logger.clone();
+        tokio::spawn(async move {
+            let signal = async {
+                #[cfg(unix)]
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {},
+                    _ = terminate.recv() => {},
+                }
+                #[cfg(not(unix))]
+                tokio::signal::ctrl_c().await.ok();
+            };
+
+            signal.await;
+            warn!(
+                logger,
+                "Stopping load, waiting up to {:.1}s for requests in flight, press Ctrl+C again to exit now",
+                grace.as_secs_f64()
+            );
+            sender.send(Some(Instant::now() + grace)).ok();
+
+            tokio::signal::ctrl_c().await.ok();
+            std::process::exit(130);
+        });
+
+        Ok(Shutdown { deadline })
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.deadline.borrow().is_some()
+    }
+
+    /// Ждем запроса остановки и возвращаем крайний срок ожидания
+    async fn deadline(&mut self) -> Instant {
+        loop {
+            if let Some(deadline) = *self.deadline.borrow_and_update() {
+                return deadline;
+            }
+            // Отправитель живет до конца приложения, без него остановки уже не будет
+            if self.deadline.changed().await.is_err() {
+                futures::future::pending::<()>().await;
+            }
+        }
+    }
+
+    /// Завершается при запросе остановки
+    pub async fn requested(&mut self) {
+        self.deadline().await;
+    }
+
+    /// Завершается после крайнего срока ожидания выполняющихся запросов
+    pub async fn grace_expired(&mut self) {
+        let deadline = self.deadline().await;
+        tokio::time::sleep_until(tokio::time::Instant::from_std(deadline)).await;
+    }
+}
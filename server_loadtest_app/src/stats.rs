@@ -0,0 +1,350 @@
+use crate::timeseries::Timeseries;
+use eyre::WrapErr;
+use hdrhistogram::Histogram;
+use owo_colors::OwoColorize;
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    time::{Duration, Instant},
+};
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedSender},
+    task::JoinHandle,
+};
+use validate_lib::{CheckError, ErrorKind, PurchaseResponse};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Максимальное записываемое время запроса в микросекундах, большие значения обрезаются
+const MAX_LATENCY_MICROS: u64 = 3_600_000_000;
+
+/// Перцентили для вывода в отчете
+const PERCENTILES: [(&str, f64); 5] = [
+    ("p50", 50.0),
+    ("p90", 90.0),
+    ("p95", 95.0),
+    ("p99", 99.0),
+    ("p99.9", 99.9),
+];
+
+/// Распределение времени выполнения запросов в микросекундах
+#[derive(Debug, Clone)]
+pub struct LatencyStats {
+    histogram: Histogram<u64>,
+}
+
+impl Default for LatencyStats {
+    fn default() -> Self {
+        LatencyStats::new()
+    }
+}
+
+impl LatencyStats {
+    pub fn new() -> LatencyStats {
+        // Три значащих цифры: погрешность не больше 0.1%
+        let histogram = Histogram::new_with_bounds(1, MAX_LATENCY_MICROS, 3)
+            .expect("Invalid latency histogram bounds");
+        LatencyStats { histogram }
+    }
+
+    pub fn record(&mut self, latency: Duration) {
+        let micros = u64::try_from(latency.as_micros()).unwrap_or(u64::MAX);
+        self.histogram.saturating_record(micros.max(1));
+    }
+
+    /// Добавляем значения другой гистограммы, например другой задачи
+    pub fn merge(&mut self, other: &LatencyStats) -> Result<(), eyre::Error> {
+        self.histogram
+            .add(&other.histogram)
+            .wrap_err("Latency histograms merge failed")
+    }
+
+    pub fn count(&self) -> u64 {
+        self.histogram.len()
+    }
+
+    /// Время в миллисекундах для вывода
+    fn millis(micros: f64) -> f64 {
+        micros / 1000.0
+    }
+
+    pub fn percentile_millis(&self, percentile: f64) -> f64 {
+        Self::millis(self.histogram.value_at_percentile(percentile) as f64)
+    }
+
+    pub fn max_millis(&self) -> f64 {
+        Self::millis(self.histogram.max() as f64)
+    }
+
+    pub fn min_millis(&self) -> f64 {
+        Self::millis(self.histogram.min() as f64)
+    }
+
+    pub fn mean_millis(&self) -> f64 {
+        Self::millis(self.histogram.mean())
+    }
+
+    pub fn stddev_millis(&self) -> f64 {
+        Self::millis(self.histogram.stdev())
+    }
+
+    /// Запросов в секунду за время прогона
+    pub fn throughput(&self, wall_time: Duration) -> f64 {
+        if wall_time.is_zero() {
+            0.0
+        } else {
+            self.count() as f64 / wall_time.as_secs_f64()
+        }
+    }
+
+    /// Выводим количество, пропускную способность и распределение времени запросов
+    pub fn print(&self, out: &mut impl Write, wall_time: Duration) -> std::io::Result<()> {
+        let count = self.count();
+        let throughput = self.throughput(wall_time);
+        writeln!(
+            out,
+            "Requests: {} in {:.2}s, throughput {} req/s",
+            count,
+            wall_time.as_secs_f64(),
+            format!("{throughput:.1}").green()
+        )?;
+
+        if count == 0 {
+            return Ok(());
+        }
+
+        writeln!(out, "Latency, mSec:")?;
+        let mut rows = vec![
+            ("min", Self::millis(self.histogram.min() as f64)),
+            ("mean", Self::millis(self.histogram.mean())),
+            ("stddev", Self::millis(self.histogram.stdev())),
+        ];
+        for (name, percentile) in PERCENTILES {
+            let value = self.histogram.value_at_percentile(percentile);
+            rows.push((name, Self::millis(value as f64)));
+        }
+        rows.push(("max", Self::millis(self.histogram.max() as f64)));
+
+        for (name, value) in rows {
+            writeln!(out, "  {:<8}{}", name, format!("{value:.3}").green())?;
+        }
+
+        Ok(())
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Класс ошибки для статистики, ошибки HTTP разделяются по коду ответа
+pub fn error_class(err: &eyre::Error) -> String {
+    match CheckError::find(err) {
+        Some(CheckError::HttpStatus { status, .. }) => {
+            format!("{} {}", ErrorKind::HttpStatus.name(), status.as_u16())
+        }
+        _ => ErrorKind::of(err).name().to_owned(),
+    }
+}
+
+/// Количество ошибок по классам
+#[derive(Debug, Default, Clone)]
+pub struct ErrorStats {
+    counts: BTreeMap<String, u64>,
+}
+
+impl ErrorStats {
+    pub fn record(&mut self, err: &eyre::Error) {
+        *self.counts.entry(error_class(err)).or_default() += 1;
+    }
+
+    pub fn merge(&mut self, other: &ErrorStats) {
+        for (class, count) in other.counts.iter() {
+            *self.counts.entry(class.clone()).or_default() += count;
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.values().sum()
+    }
+
+    pub fn counts(&self) -> &BTreeMap<String, u64> {
+        &self.counts
+    }
+
+    /// Выводим количество и долю ошибок каждого класса
+    pub fn print(&self, out: &mut impl Write, requests_count: u64) -> std::io::Result<()> {
+        let percent = |count: u64| {
+            if requests_count == 0 {
+                0.0
+            } else {
+                count as f64 * 100.0 / requests_count as f64
+            }
+        };
+
+        let total = self.total();
+        let total_text = format!("{} ({:.2}%)", total, percent(total));
+        if total == 0 {
+            writeln!(out, "Errors: {}", total_text.green())?;
+        } else {
+            writeln!(out, "Errors: {}", total_text.red())?;
+        }
+        for (class, count) in self.counts.iter() {
+            writeln!(
+                out,
+                "  {:<20}{} ({:.2}%)",
+                class,
+                count.red(),
+                percent(*count)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Статистика нагрузочного прогона: время всех запросов и ошибки
+#[derive(Debug, Default, Clone)]
+pub struct RunStats {
+    pub latency: LatencyStats,
+    pub errors: ErrorStats,
+}
+
+impl RunStats {
+    /// Учитываем выполненный запрос, время учитывается и для запросов с ошибкой
+    pub fn record(&mut self, result: Result<&PurchaseResponse, &eyre::Error>, latency: Duration) {
+        self.latency.record(latency);
+        if let Err(err) = result {
+            self.errors.record(err);
+        }
+    }
+
+    pub fn merge(&mut self, other: &RunStats) -> Result<(), eyre::Error> {
+        self.latency.merge(&other.latency)?;
+        self.errors.merge(&other.errors);
+        Ok(())
+    }
+
+    /// Доля запросов с ошибкой в процентах
+    pub fn error_rate(&self) -> f64 {
+        match self.latency.count() {
+            0 => 0.0,
+            count => self.errors.total() as f64 * 100.0 / count as f64,
+        }
+    }
+
+    pub fn print(&self, out: &mut impl Write, wall_time: Duration) -> std::io::Result<()> {
+        self.latency.print(out, wall_time)?;
+        self.errors.print(out, self.latency.count())
+    }
+
+    /// Выводим статистику одной строкой, например для этапа профиля
+    pub fn print_brief(
+        &self,
+        out: &mut impl Write,
+        title: &str,
+        wall_time: Duration,
+    ) -> std::io::Result<()> {
+        let count = self.latency.count();
+        let throughput = self.latency.throughput(wall_time);
+        let errors_text = format!("{} ({:.2}%)", self.errors.total(), self.error_rate());
+        let errors_text = if self.errors.total() == 0 {
+            errors_text.green().to_string()
+        } else {
+            errors_text.red().to_string()
+        };
+        writeln!(
+            out,
+            "  {}: requests {}, {:.1} req/s, p50 {:.3}, p99 {:.3}, max {:.3} mSec, errors {}",
+            title,
+            count,
+            throughput,
+            self.latency.percentile_millis(50.0),
+            self.latency.percentile_millis(99.0),
+            self.latency.max_millis(),
+            errors_text
+        )
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Результат одного запроса нагрузки
+#[derive(Debug)]
+pub struct RequestOutcome {
+    pub stage: usize,
+    pub config_index: usize,
+    pub test_index: usize,
+    pub result: Result<PurchaseResponse, eyre::Error>,
+    pub latency: Duration,
+}
+
+/// Статистика прогона по этапам профиля и по тестам конфигов
+#[derive(Debug)]
+pub struct LoadResults {
+    pub stages: Vec<RunStats>,
+    /// Ключ - номер конфига и номер теста в нем
+    pub tests: BTreeMap<(usize, usize), RunStats>,
+    pub timeseries: Timeseries,
+}
+
+impl LoadResults {
+    pub fn new(stages_count: usize) -> LoadResults {
+        LoadResults {
+            stages: vec![RunStats::default(); stages_count],
+            tests: BTreeMap::new(),
+            timeseries: Timeseries::default(),
+        }
+    }
+
+    /// Учитываем запрос, завершенный через elapsed от начала прогона
+    pub fn record(&mut self, outcome: &RequestOutcome, elapsed: Duration) {
+        let result = outcome.result.as_ref();
+        self.timeseries.record(elapsed, result, outcome.latency);
+        self.stages[outcome.stage].record(result, outcome.latency);
+        self.tests
+            .entry((outcome.config_index, outcome.test_index))
+            .or_default()
+            .record(result, outcome.latency);
+    }
+
+    /// Общая статистика по всем этапам
+    pub fn total(&self) -> Result<RunStats, eyre::Error> {
+        let mut total = RunStats::default();
+        for stats in self.stages.iter() {
+            total.merge(stats)?;
+        }
+        Ok(total)
+    }
+}
+
+/// Задача сбора результатов запросов параллельно с нагрузкой
+pub struct StatsCollector {
+    sender: UnboundedSender<RequestOutcome>,
+    join: JoinHandle<LoadResults>,
+}
+
+impl StatsCollector {
+    /// Время завершения запросов для посекундных показателей считаем от begin_time
+    pub fn spawn(stages_count: usize, begin_time: Instant) -> StatsCollector {
+        let (sender, mut receiver) = unbounded_channel::<RequestOutcome>();
+        let join = tokio::spawn(async move {
+            let mut results = LoadResults::new(stages_count);
+            while let Some(outcome) = receiver.recv().await {
+                results.record(&outcome, begin_time.elapsed());
+            }
+            results.timeseries.finish();
+            results
+        });
+        StatsCollector { sender, join }
+    }
+
+    pub fn sender(&self) -> UnboundedSender<RequestOutcome> {
+        self.sender.clone()
+    }
+
+    /// Ждем результатов от всех отправителей
+    pub async fn finish(self) -> Result<LoadResults, eyre::Error> {
+        drop(self.sender);
+        self.join.await.wrap_err("Stats collector join failed")
+    }
+}
@@ -0,0 +1,114 @@
+use crate::stats::RunStats;
+use eyre::WrapErr;
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+use validate_lib::PurchaseResponse;
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Показатели за одну секунду прогона
+#[derive(Debug, Clone)]
+pub struct TimeseriesPoint {
+    /// Номер секунды от начала прогона
+    pub second: u64,
+    pub requests: u64,
+    pub errors: u64,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
+impl TimeseriesPoint {
+    fn new(second: u64, stats: &RunStats) -> TimeseriesPoint {
+        let latency = &stats.latency;
+        TimeseriesPoint {
+            second,
+            requests: latency.count(),
+            errors: stats.errors.total(),
+            mean_ms: latency.mean_millis(),
+            p50_ms: latency.percentile_millis(50.0),
+            p90_ms: latency.percentile_millis(90.0),
+            p99_ms: latency.percentile_millis(99.0),
+            max_ms: latency.max_millis(),
+        }
+    }
+}
+
+/// Посекундные показатели по времени завершения запросов.
+/// Хранятся только итоговые значения секунд, чтобы долгие прогоны не занимали много памяти.
+#[derive(Debug, Default)]
+pub struct Timeseries {
+    current_second: u64,
+    current: RunStats,
+    pub points: Vec<TimeseriesPoint>,
+}
+
+impl Timeseries {
+    /// Учитываем запрос, завершенный через elapsed от начала прогона
+    pub fn record(
+        &mut self,
+        elapsed: Duration,
+        result: Result<&PurchaseResponse, &eyre::Error>,
+        latency: Duration,
+    ) {
+        let second = elapsed.as_secs();
+        // Завершаем прошедшие секунды, включая секунды без запросов
+        while self.current_second < second {
+            self.close_current();
+        }
+        self.current.record(result, latency);
+    }
+
+    fn close_current(&mut self) {
+        let stats = std::mem::take(&mut self.current);
+        self.points
+            .push(TimeseriesPoint::new(self.current_second, &stats));
+        self.current_second += 1;
+    }
+
+    /// Завершаем последнюю секунду после окончания прогона
+    pub fn finish(&mut self) {
+        if self.current.latency.count() > 0 {
+            self.close_current();
+        }
+    }
+}
+
+/// Сохраняем посекундные показатели в CSV
+pub fn write_timeseries_csv(path: &Path, points: &[TimeseriesPoint]) -> Result<(), eyre::Error> {
+    let file = File::create(path)
+        .wrap_err_with(|| format!("Timeseries file {} create failed", path.display()))?;
+    let mut out = BufWriter::new(file);
+
+    writeln!(
+        out,
+        "second,requests,errors,error_rate,mean_ms,p50_ms,p90_ms,p99_ms,max_ms"
+    )?;
+    for point in points {
+        let error_rate = match point.requests {
+            0 => 0.0,
+            requests => point.errors as f64 * 100.0 / requests as f64,
+        };
+        writeln!(
+            out,
+            "{},{},{},{:.4},{:.3},{:.3},{:.3},{:.3},{:.3}",
+            point.second,
+            point.requests,
+            point.errors,
+            error_rate,
+            point.mean_ms,
+            point.p50_ms,
+            point.p90_ms,
+            point.p99_ms,
+            point.max_ms
+        )?;
+    }
+    out.flush()
+        .wrap_err_with(|| format!("Timeseries file {} write failed", path.display()))
+}
@@ -0,0 +1,129 @@
+use crate::{
+    error::{CheckError, ErrorKind},
+    project_info::ProjectInfo,
+    test_case::TestCase,
+    validation::PurchaseResponse,
+};
+use serde::Serialize;
+use std::{str::FromStr, time::Duration};
+
+/// Формат вывода результатов: текст для человека или JSON Lines с записью на каждую проверку
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Text,
+    Jsonl,
+}
+
+impl FromStr for OutputFormat {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "jsonl" => Ok(OutputFormat::Jsonl),
+            _ => Err(eyre::eyre!(
+                "Unknown output format {}, only text/jsonl are supported",
+                s
+            )),
+        }
+    }
+}
+
+/// Результат одной выполненной проверки для машиночитаемого вывода в формате JSON Lines.
+///
+/// Каждая проверка выводится отдельной строкой с JSON объектом, например:
+///
+/// ```text
+/// {"project":"island2","index":3,"name":null,"scenario":null,"platform":"google","product_id":"coins_1","order_id":"GPA.1","expected_status":"ok","received_status":"already_used","http_status":200,"latency_ms":154.2,"passed":false,"error_kind":"status_mismatch","error":"Status invalid: received already_used != required ok","attempts":1}
+/// ```
+///
+/// Поля со значением null присутствуют всегда. Существующие поля не переименовываются
+/// и не удаляются, новые поля могут добавляться в конец.
+#[derive(Debug, Serialize)]
+pub struct CheckRecord {
+    /// Имя проекта, к которому был выполнен запрос
+    pub project: String,
+
+    /// Номер теста в конфиге или шага в сценарии, начиная с 1
+    pub index: usize,
+
+    /// Имя теста или шага сценария
+    pub name: Option<String>,
+
+    /// Имя сценария для шагов сценариев
+    pub scenario: Option<String>,
+
+    pub platform: String,
+    pub product_id: String,
+    pub order_id: String,
+
+    /// Ожидаемый статус покупки
+    pub expected_status: String,
+
+    /// Полученный от сервера статус покупки, если ответ удалось разобрать
+    pub received_status: Option<String>,
+
+    /// HTTP статус ответа, если ответ был получен
+    pub http_status: Option<u16>,
+
+    /// Время выполнения запроса в миллисекундах
+    pub latency_ms: f64,
+
+    /// Прошла ли проверка
+    pub passed: bool,
+
+    /// Класс ошибки: transport, timeout, http_status, invalid_response, signature_mismatch, status_mismatch
+    pub error_kind: Option<ErrorKind>,
+
+    /// Полная цепочка ошибок одной строкой
+    pub error: Option<String>,
+
+    /// Количество выполненных попыток, больше 1 при повторах упавших проверок
+    pub attempts: u32,
+}
+
+impl CheckRecord {
+    pub fn new(
+        project: &ProjectInfo,
+        index: usize,
+        test: &TestCase,
+        result: Result<&PurchaseResponse, &eyre::Error>,
+        latency: Duration,
+    ) -> CheckRecord {
+        // Даже в случае ошибки часть данных ответа может быть известна
+        let (response, http_status, error) = match result {
+            Ok(response) => (Some(response), Some(response.http_status), None),
+            Err(err) => match CheckError::find(err) {
+                Some(CheckError::StatusMismatch { response, .. }) => {
+                    (Some(response), Some(response.http_status), Some(err))
+                }
+                Some(CheckError::HttpStatus { status, .. }) => (None, Some(*status), Some(err)),
+                _ => (None, None, Some(err)),
+            },
+        };
+
+        CheckRecord {
+            project: project.name.clone(),
+            index,
+            name: test.name.clone(),
+            scenario: None,
+            platform: test.purchase.platform.clone(),
+            product_id: test.purchase.product_id.clone(),
+            order_id: test.purchase.order_id.clone(),
+            expected_status: test.response.status.clone(),
+            received_status: response.map(|response| response.purchase_status.status.clone()),
+            http_status: http_status.map(|status| status.as_u16()),
+            latency_ms: latency.as_secs_f64() * 1000.0,
+            passed: error.is_none(),
+            error_kind: error.map(ErrorKind::of),
+            error: error.map(|err| format!("{err:#}")),
+            attempts: 1,
+        }
+    }
+
+    /// Строка для вывода в формате JSON Lines
+    pub fn to_json_line(&self) -> String {
+        // Сериализация структуры из строк и чисел не может завершиться ошибкой
+        serde_json::to_string(self).expect("Check record serialize failed")
+    }
+}
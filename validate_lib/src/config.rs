@@ -1,18 +1,54 @@
-use super::{project_info::ProjectInfo, TestCase};
+use super::{
+    project_info::ProjectInfo,
+    scenario::{Scenario, ScenarioTemplate},
+    test_template::{default_weight, TestCaseTemplate},
+    TestCase,
+};
+use eyre::WrapErr;
 use serde::Deserialize;
 use std::{fs::File, io::BufReader, path::Path};
 
+/// Конфиг в том виде, в котором он лежит в файлике
 #[derive(Deserialize, Debug)]
+struct RawConfig {
+    project: ProjectInfo,
+    #[serde(default)]
+    tests: Vec<TestCaseTemplate>,
+    #[serde(default)]
+    scenarios: Vec<ScenarioTemplate>,
+    #[serde(default)]
+    load: LoadSettings,
+}
+
+/// Параметры конфига для нагрузочного тестирования
+#[derive(Deserialize, Debug, Clone)]
+pub struct LoadSettings {
+    /// Относительная доля конфига в нагрузке среди всех конфигов
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+}
+
+impl Default for LoadSettings {
+    fn default() -> Self {
+        LoadSettings {
+            weight: default_weight(),
+        }
+    }
+}
+
+#[derive(Debug)]
 pub struct Config {
     pub project: ProjectInfo,
     pub tests: Vec<TestCase>,
+    pub scenarios: Vec<Scenario>,
+    pub load: LoadSettings,
 }
 
 impl Config {
     /// Пытаемся распасить конфиг из файлика
     pub fn parse_from_file(path: &Path) -> Result<Config, eyre::Error> {
         // Пробуем загрузить конфиг из файлика в зависимости от расширения
-        let config: Config = match path
+        let config: RawConfig = match path
             .extension()
             .and_then(|v| v.to_str())
             .map(str::to_lowercase)
@@ -33,6 +69,39 @@ impl Config {
                 ));
             }
         };
-        Ok(config)
+
+        eyre::ensure!(
+            config.load.weight.is_finite() && config.load.weight >= 0.0,
+            "Config load weight cannot be negative"
+        );
+
+        // Файлики с чеками ищем рядом с конфигом
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+        // Разворачиваем матрицы параметров в отдельные тесты
+        let mut tests = Vec::with_capacity(config.tests.len());
+        for (i, template) in config.tests.into_iter().enumerate() {
+            let index = i + 1;
+            let expanded = template
+                .expand(index, base_dir)
+                .wrap_err_with(|| format!("Test {index} expand failed"))?;
+            tests.extend(expanded);
+        }
+
+        // Подставляем в шаги сценариев данные из предыдущих шагов
+        let mut scenarios = Vec::with_capacity(config.scenarios.len());
+        for (i, template) in config.scenarios.into_iter().enumerate() {
+            let scenario = template
+                .resolve(base_dir)
+                .wrap_err_with(|| format!("Scenario {} resolve failed", i + 1))?;
+            scenarios.push(scenario);
+        }
+
+        Ok(Config {
+            project: config.project,
+            tests,
+            scenarios,
+            load: config.load,
+        })
     }
 }
@@ -0,0 +1,51 @@
+use eyre::WrapErr;
+use serde::Deserialize;
+use std::path::Path;
+
+/// Как превращать содержимое файлика в строку
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FileEncoding {
+    /// Текст как есть, если файлик в UTF-8, иначе base64
+    #[default]
+    Auto,
+    /// Текст как есть
+    Text,
+    /// Всегда кодируем в base64
+    Base64,
+}
+
+impl std::str::FromStr for FileEncoding {
+    type Err = eyre::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(FileEncoding::Auto),
+            "text" => Ok(FileEncoding::Text),
+            "base64" => Ok(FileEncoding::Base64),
+            _ => Err(eyre::eyre!(
+                "Unknown file encoding {}, only auto/text/base64 are supported",
+                s
+            )),
+        }
+    }
+}
+
+/// Читаем файлик с данными (например, чек покупки) в строку.
+/// У текста отрезаются завершающие переводы строк, которые добавляют редакторы.
+pub fn read_data_file(path: &Path, encoding: FileEncoding) -> Result<String, eyre::Error> {
+    let data =
+        std::fs::read(path).wrap_err_with(|| format!("File {} read failed", path.display()))?;
+
+    let text = match encoding {
+        FileEncoding::Base64 => base64::encode(data),
+        FileEncoding::Text => String::from_utf8(data)
+            .wrap_err_with(|| format!("File {} is not valid UTF-8 text", path.display()))?,
+        FileEncoding::Auto => match String::from_utf8(data) {
+            Ok(text) => text,
+            Err(err) => base64::encode(err.into_bytes()),
+        },
+    };
+
+    Ok(text.trim_end_matches(['\r', '\n']).to_owned())
+}
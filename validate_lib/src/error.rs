@@ -0,0 +1,120 @@
+use crate::validation::PurchaseResponse;
+use reqwest::StatusCode;
+use serde::Serialize;
+use std::fmt::{self, Display};
+
+/// Ошибки проверки покупки, которые нужно уметь отличать от остальных
+#[derive(Debug)]
+pub enum CheckError {
+    /// Сервер ответил неуспешным HTTP статусом
+    HttpStatus {
+        status: StatusCode,
+        text: Option<String>,
+    },
+
+    /// Подпись ответа сервера не совпала с вычисленной
+    SignatureMismatch {
+        calculated: String,
+        received: String,
+    },
+
+    /// Статус покупки не совпал с ожидаемым, ответ сервера сохраняем целиком
+    StatusMismatch {
+        response: PurchaseResponse,
+        required: String,
+    },
+}
+
+impl Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckError::HttpStatus {
+                status,
+                text: Some(text),
+            } => write!(
+                f,
+                "Server response with status {} and text: {}",
+                status, text
+            ),
+            CheckError::HttpStatus { status, text: None } => {
+                write!(f, "Server response with status {}", status)
+            }
+            CheckError::SignatureMismatch {
+                calculated,
+                received,
+            } => write!(
+                f,
+                "Response signature invalid: calculated {} != received {}",
+                calculated, received
+            ),
+            CheckError::StatusMismatch { response, required } => write!(
+                f,
+                "Status invalid: received {} != required {}",
+                response.purchase_status.status, required
+            ),
+        }
+    }
+}
+
+impl std::error::Error for CheckError {}
+
+impl CheckError {
+    /// Ищем среди цепочки ошибок ошибку проверки
+    pub fn find(err: &eyre::Error) -> Option<&CheckError> {
+        err.chain().find_map(|err| err.downcast_ref::<CheckError>())
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Класс ошибки проверки для статистики и машиночитаемого вывода
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Не удалось выполнить запрос или получить ответ
+    Transport,
+    /// Истекло время ожидания запроса
+    Timeout,
+    /// Сервер ответил неуспешным HTTP статусом
+    HttpStatus,
+    /// Ответ сервера не удалось разобрать
+    InvalidResponse,
+    /// Подпись ответа не совпала
+    SignatureMismatch,
+    /// Статус покупки не совпал с ожидаемым
+    StatusMismatch,
+}
+
+impl ErrorKind {
+    /// Определяем класс ошибки по цепочке ошибок
+    pub fn of(err: &eyre::Error) -> ErrorKind {
+        if let Some(check_error) = CheckError::find(err) {
+            return match check_error {
+                CheckError::HttpStatus { .. } => ErrorKind::HttpStatus,
+                CheckError::SignatureMismatch { .. } => ErrorKind::SignatureMismatch,
+                CheckError::StatusMismatch { .. } => ErrorKind::StatusMismatch,
+            };
+        }
+
+        let transport_error = err
+            .chain()
+            .find_map(|err| err.downcast_ref::<reqwest::Error>());
+        match transport_error {
+            Some(transport_error) if transport_error.is_timeout() => ErrorKind::Timeout,
+            Some(_) => ErrorKind::Transport,
+            None => ErrorKind::InvalidResponse,
+        }
+    }
+
+    /// Имя класса, совпадает с сериализованным
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorKind::Transport => "transport",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::HttpStatus => "http_status",
+            ErrorKind::InvalidResponse => "invalid_response",
+            ErrorKind::SignatureMismatch => "signature_mismatch",
+            ErrorKind::StatusMismatch => "status_mismatch",
+        }
+    }
+}
@@ -1,8 +1,23 @@
+mod check_record;
 mod config;
+mod data_file;
+mod error;
 mod project_info;
+mod scenario;
 mod test_case;
+mod test_template;
 mod validation;
 
 pub use crate::{
-    config::Config, project_info::ProjectInfo, test_case::TestCase, validation::check_purchase,
+    check_record::{CheckRecord, OutputFormat},
+    config::{Config, LoadSettings},
+    data_file::{read_data_file, FileEncoding},
+    error::{CheckError, ErrorKind},
+    project_info::ProjectInfo,
+    scenario::{Scenario, ScenarioStep},
+    test_case::{PurchaseData, ResponseData, TestCase},
+    validation::{
+        build_request_body, calc_signature, check_purchase, request_purchase, JsonRequestBody,
+        PurchaseResponse, PurchaseStatus,
+    },
 };
@@ -0,0 +1,122 @@
+use crate::{
+    project_info::ProjectInfo,
+    test_case::{ResponseData, TestCase},
+    test_template::{
+        normalize_fields, override_fields, purchase_from_fields, resolve_file_fields,
+        PurchaseFields,
+    },
+};
+use eyre::{ContextCompat, WrapErr};
+use serde::Deserialize;
+use std::path::Path;
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Шаг сценария в том виде, в котором он лежит в конфиге
+#[derive(Deserialize, Debug)]
+struct ScenarioStepTemplate {
+    #[serde(default)]
+    name: Option<String>,
+
+    /// Другой проект для шага, по умолчанию используется проект конфига
+    #[serde(default)]
+    project: Option<ProjectInfo>,
+
+    /// Имя предыдущего шага, данные покупки которого берем за основу
+    #[serde(default)]
+    purchase_from: Option<String>,
+
+    /// Данные покупки, перекрывают данные из purchase_from
+    #[serde(default)]
+    purchase: PurchaseFields,
+
+    response: ResponseData,
+}
+
+/// Сценарий в том виде, в котором он лежит в конфиге
+#[derive(Deserialize, Debug)]
+pub(crate) struct ScenarioTemplate {
+    name: String,
+    steps: Vec<ScenarioStepTemplate>,
+}
+
+impl ScenarioTemplate {
+    /// Получаем итоговый сценарий, подставляя данные из предыдущих шагов.
+    /// Пути к файликам с чеками считаются относительно base_dir.
+    pub(crate) fn resolve(self, base_dir: &Path) -> Result<Scenario, eyre::Error> {
+        eyre::ensure!(!self.steps.is_empty(), "Scenario steps cannot be empty");
+
+        // Итоговые поля покупки уже пройденных шагов
+        let mut resolved_fields: Vec<(String, PurchaseFields)> = Vec::new();
+
+        let mut steps = Vec::with_capacity(self.steps.len());
+        for (i, step) in self.steps.into_iter().enumerate() {
+            let name = step.name.unwrap_or_else(|| format!("step_{}", i + 1));
+
+            eyre::ensure!(
+                resolved_fields
+                    .iter()
+                    .all(|(prev_name, _)| prev_name != &name),
+                "Step name {} is not unique",
+                name
+            );
+
+            // Берем за основу данные одного из предыдущих шагов
+            let mut fields = match step.purchase_from {
+                Some(from) => resolved_fields
+                    .iter()
+                    .find(|(prev_name, _)| prev_name == &from)
+                    .map(|(_, fields)| fields.clone())
+                    .wrap_err_with(|| {
+                        format!("Step {name} refers to unknown previous step {from}")
+                    })?,
+                None => PurchaseFields::new(),
+            };
+            override_fields(&mut fields, normalize_fields(step.purchase));
+            resolve_file_fields(&mut fields, base_dir)
+                .wrap_err_with(|| format!("Step {name} files load failed"))?;
+
+            let purchase = purchase_from_fields(fields.clone(), base_dir)
+                .wrap_err_with(|| format!("Step {name} is invalid"))?;
+
+            steps.push(ScenarioStep {
+                project: step.project,
+                test: TestCase {
+                    name: Some(name.clone()),
+                    purchase,
+                    response: step.response,
+                    weight: 1.0,
+                },
+            });
+            resolved_fields.push((name, fields));
+        }
+
+        Ok(Scenario {
+            name: self.name,
+            steps,
+        })
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Шаг сценария: обычный тест, возможно к другому проекту
+#[derive(Debug)]
+pub struct ScenarioStep {
+    pub project: Option<ProjectInfo>,
+    pub test: TestCase,
+}
+
+impl ScenarioStep {
+    /// Проект шага с учетом проекта по умолчанию
+    pub fn project<'a>(&'a self, default: &'a ProjectInfo) -> &'a ProjectInfo {
+        self.project.as_ref().unwrap_or(default)
+    }
+}
+
+/// Последовательность шагов, выполняемых строго по порядку
+#[derive(Debug)]
+pub struct Scenario {
+    pub name: String,
+    pub steps: Vec<ScenarioStep>,
+}
@@ -1,7 +1,7 @@
 use helpers_lib::deserialize_string_not_empty;
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct PurchaseData {
     #[serde(deserialize_with = "deserialize_string_not_empty")]
     pub platform: String,
@@ -17,19 +17,23 @@ pub struct PurchaseData {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub receipt_signature: Option<String>,
-    
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_identifier: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct ResponseData {
     #[serde(deserialize_with = "deserialize_string_not_empty")]
     pub status: String,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Debug, Clone)]
 pub struct TestCase {
+    /// Имя теста, для тестов из матрицы генерируется автоматически
+    pub name: Option<String>,
     pub purchase: PurchaseData,
     pub response: ResponseData,
+    /// Относительная доля теста в нагрузке среди тестов той же платформы конфига
+    pub weight: f64,
 }
@@ -0,0 +1,384 @@
+use crate::{
+    data_file::{read_data_file, FileEncoding},
+    test_case::{PurchaseData, ResponseData, TestCase},
+};
+use eyre::WrapErr;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Поля покупки в сыром виде до подстановки значений из матрицы
+pub(crate) type PurchaseFields = Map<String, Value>;
+
+/// Ссылка на файлик с данными вместо самих данных в конфиге
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum FileSource {
+    Path(PathBuf),
+    WithEncoding {
+        path: PathBuf,
+        #[serde(default)]
+        encoding: FileEncoding,
+    },
+}
+
+/// Поля покупки, которые можно задавать в конфиге
+const PURCHASE_FIELDS: [&str; 6] = [
+    "platform",
+    "product_id",
+    "order_id",
+    "receipt",
+    "receipt_signature",
+    "client_identifier",
+];
+
+/// Поля со ссылками на файлики и поля, в которые подставляется содержимое
+const FILE_FIELDS: [(&str, &str); 2] = [
+    ("receipt_file", "receipt"),
+    ("receipt_signature_file", "receipt_signature"),
+];
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Значение параметра матрицы, опционально со своим ожидаемым статусом.
+/// Числа и булевы значения допустимы так же, как в полях покупки.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum MatrixValue {
+    WithStatus { value: Value, status: String },
+    Plain(Value),
+}
+
+impl MatrixValue {
+    fn value(&self) -> Result<String, eyre::Error> {
+        let value = match self {
+            MatrixValue::Plain(value) => value,
+            MatrixValue::WithStatus { value, .. } => value,
+        };
+        match normalize_value(value.clone()) {
+            Value::String(value) => Ok(value),
+            other => Err(eyre::eyre!(
+                "Matrix value {} must be a string, number or bool",
+                other
+            )),
+        }
+    }
+
+    fn status(&self) -> Option<&str> {
+        match self {
+            MatrixValue::Plain(_) => None,
+            MatrixValue::WithStatus { status, .. } => Some(status),
+        }
+    }
+}
+
+/// Матрица параметров: имя поля покупки -> список значений.
+/// Тесты генерируются для всех сочетаний значений.
+#[derive(Deserialize, Debug)]
+#[serde(transparent)]
+struct TestMatrix(BTreeMap<String, Vec<MatrixValue>>);
+
+impl TestMatrix {
+    /// Все сочетания значений параметров
+    fn combinations(&self) -> Result<Vec<Vec<(&str, &MatrixValue)>>, eyre::Error> {
+        eyre::ensure!(!self.0.is_empty(), "Matrix cannot be empty");
+
+        // Неизвестные поля PurchaseData молча игнорирует, поэтому опечатка дала бы одинаковые тесты
+        for key in self.0.keys() {
+            let known = PURCHASE_FIELDS.contains(&key.as_str())
+                || FILE_FIELDS.iter().any(|(file_key, _)| file_key == key);
+            eyre::ensure!(known, "Matrix key '{}' is not a purchase field", key);
+        }
+
+        let mut result = vec![Vec::new()];
+        for (key, values) in self.0.iter() {
+            eyre::ensure!(
+                !values.is_empty(),
+                "Matrix values for '{}' cannot be empty",
+                key
+            );
+
+            result = result
+                .into_iter()
+                .flat_map(|prefix: Vec<(&str, &MatrixValue)>| {
+                    values.iter().map(move |value| {
+                        let mut combination = prefix.clone();
+                        combination.push((key.as_str(), value));
+                        combination
+                    })
+                })
+                .collect();
+        }
+        Ok(result)
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Описание теста в конфиге, из которого получается один или несколько тестов
+#[derive(Deserialize, Debug)]
+pub(crate) struct TestCaseTemplate {
+    #[serde(default)]
+    name: Option<String>,
+    purchase: PurchaseFields,
+    response: ResponseData,
+    #[serde(default)]
+    matrix: Option<TestMatrix>,
+    /// Вес в нагрузке, для матрицы у каждого сгенерированного теста
+    #[serde(default = "default_weight")]
+    weight: f64,
+}
+
+pub(crate) fn default_weight() -> f64 {
+    1.0
+}
+
+impl TestCaseTemplate {
+    /// Разворачиваем описание в список тестов, index нужен для генерации имени,
+    /// пути к файликам с чеками считаются относительно base_dir
+    pub(crate) fn expand(
+        self,
+        index: usize,
+        base_dir: &Path,
+    ) -> Result<Vec<TestCase>, eyre::Error> {
+        let TestCaseTemplate {
+            name,
+            purchase,
+            response,
+            matrix,
+            weight,
+        } = self;
+
+        eyre::ensure!(
+            weight.is_finite() && weight >= 0.0,
+            "Test weight cannot be negative"
+        );
+
+        let purchase = normalize_fields(purchase);
+
+        // Без матрицы у нас просто один тест
+        let matrix = match matrix {
+            Some(matrix) => matrix,
+            None => {
+                return Ok(vec![TestCase {
+                    name,
+                    purchase: purchase_from_fields(purchase, base_dir)?,
+                    response,
+                    weight,
+                }]);
+            }
+        };
+
+        let base_name = name.unwrap_or_else(|| format!("test_{index}"));
+
+        let combinations = matrix
+            .combinations()
+            .wrap_err_with(|| format!("Matrix of test {base_name} is invalid"))?;
+
+        let mut tests = Vec::new();
+        for combination in combinations {
+            let mut fields = purchase.clone();
+            let mut status: Option<&str> = None;
+            let mut params = Vec::with_capacity(combination.len());
+
+            for (key, value) in combination {
+                let value_text = value
+                    .value()
+                    .wrap_err_with(|| format!("Matrix of test {base_name} is invalid"))?;
+                params.push(format!("{key}={value_text}"));
+                fields.insert(key.to_owned(), Value::String(value_text));
+
+                // Статус может задать любое значение, но без противоречий
+                if let Some(value_status) = value.status() {
+                    match status {
+                        Some(prev_status) if prev_status != value_status => {
+                            return Err(eyre::eyre!(
+                                "Matrix values specify conflicting statuses {} and {} for [{}]",
+                                prev_status,
+                                value_status,
+                                params.join(", ")
+                            ));
+                        }
+                        _ => status = Some(value_status),
+                    }
+                }
+            }
+
+            let name = format!("{base_name}[{}]", params.join(", "));
+            let purchase = purchase_from_fields(fields, base_dir)
+                .wrap_err_with(|| format!("Matrix test {name} is invalid"))?;
+            let response = match status {
+                Some(status) => ResponseData {
+                    status: status.to_owned(),
+                },
+                None => response.clone(),
+            };
+
+            tests.push(TestCase {
+                name: Some(name),
+                purchase,
+                response,
+                weight,
+            });
+        }
+
+        Ok(tests)
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// Все поля покупки строковые, поэтому числа и булевы значения из конфига приводим к строкам
+pub(crate) fn normalize_fields(fields: PurchaseFields) -> PurchaseFields {
+    fields
+        .into_iter()
+        .map(|(key, value)| (key, normalize_value(value)))
+        .collect()
+}
+
+fn normalize_value(value: Value) -> Value {
+    match value {
+        Value::Number(number) => Value::String(number.to_string()),
+        Value::Bool(flag) => Value::String(flag.to_string()),
+        other => other,
+    }
+}
+
+/// Перекрываем поля покупки новыми значениями,
+/// ссылка на файлик и сами данные считаются одним полем
+pub(crate) fn override_fields(fields: &mut PurchaseFields, overrides: PurchaseFields) {
+    for (file_key, key) in FILE_FIELDS {
+        if overrides.contains_key(file_key) || overrides.contains_key(key) {
+            fields.remove(file_key);
+            fields.remove(key);
+        }
+    }
+    fields.extend(overrides);
+}
+
+/// Подставляем содержимое файликов вместо ссылок на них
+pub(crate) fn resolve_file_fields(
+    fields: &mut PurchaseFields,
+    base_dir: &Path,
+) -> Result<(), eyre::Error> {
+    for (file_key, key) in FILE_FIELDS {
+        let source = match fields.remove(file_key) {
+            Some(source) => source,
+            None => continue,
+        };
+
+        eyre::ensure!(
+            !fields.contains_key(key),
+            "Only one of {} and {} can be specified",
+            key,
+            file_key
+        );
+
+        let (path, encoding) = match serde_json::from_value(source)
+            .wrap_err_with(|| format!("Invalid {file_key} value"))?
+        {
+            FileSource::Path(path) => (path, FileEncoding::default()),
+            FileSource::WithEncoding { path, encoding } => (path, encoding),
+        };
+
+        let data = read_data_file(&base_dir.join(path), encoding)
+            .wrap_err_with(|| format!("Field {file_key} load failed"))?;
+        fields.insert(key.to_owned(), Value::String(data));
+    }
+    Ok(())
+}
+
+/// Собираем итоговые данные покупки из сырых полей
+pub(crate) fn purchase_from_fields(
+    mut fields: PurchaseFields,
+    base_dir: &Path,
+) -> Result<PurchaseData, eyre::Error> {
+    resolve_file_fields(&mut fields, base_dir)?;
+    serde_json::from_value(Value::Object(fields)).wrap_err("Purchase data parsing failed")
+}
+
+//////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_yaml(text: &str) -> Result<Vec<TestCase>, eyre::Error> {
+        let template: TestCaseTemplate = serde_yaml::from_str(text)?;
+        template.expand(0, Path::new("."))
+    }
+
+    const PURCHASE: &str = r#"
+purchase:
+  platform: google
+  product_id: product
+  order_id: order
+  receipt: receipt
+response:
+  status: ok
+"#;
+
+    #[test]
+    fn test_matrix_combinations_count_and_names() {
+        let text =
+            format!("name: buy\n{PURCHASE}matrix:\n  product_id: [a, b, c]\n  order_id: [x, y]\n");
+        let tests = expand_yaml(&text).unwrap();
+        assert_eq!(tests.len(), 6);
+
+        let names: Vec<&str> = tests.iter().map(|t| t.name.as_deref().unwrap()).collect();
+        assert_eq!(names[0], "buy[order_id=x, product_id=a]");
+        assert_eq!(names[5], "buy[order_id=y, product_id=c]");
+        assert_eq!(tests[1].purchase.order_id, "x");
+        assert_eq!(tests[1].purchase.product_id, "b");
+    }
+
+    #[test]
+    fn test_matrix_default_name() {
+        let text = format!("{PURCHASE}matrix:\n  product_id: [a]\n");
+        let tests = expand_yaml(&text).unwrap();
+        assert_eq!(tests[0].name.as_deref(), Some("test_0[product_id=a]"));
+    }
+
+    #[test]
+    fn test_matrix_value_status() {
+        let text = format!(
+            "{PURCHASE}matrix:\n  product_id:\n    - a\n    - {{value: b, status: invalid}}\n"
+        );
+        let tests = expand_yaml(&text).unwrap();
+        assert_eq!(tests[0].response.status, "ok");
+        assert_eq!(tests[1].response.status, "invalid");
+    }
+
+    #[test]
+    fn test_matrix_conflicting_statuses() {
+        let text = format!(
+            "{PURCHASE}matrix:\n  product_id: [{{value: a, status: invalid}}]\n  order_id: [{{value: x, status: expired}}]\n"
+        );
+        let err = expand_yaml(&text).unwrap_err();
+        assert!(err.to_string().contains("conflicting statuses"));
+    }
+
+    #[test]
+    fn test_matrix_unknown_key() {
+        let text = format!("name: buy\n{PURCHASE}matrix:\n  product_idd: [a, b, c]\n");
+        let err = expand_yaml(&text).unwrap_err();
+        assert!(err.to_string().contains("buy"));
+        assert!(format!("{err:?}").contains("product_idd"));
+    }
+
+    #[test]
+    fn test_matrix_scalar_values() {
+        let text = format!("{PURCHASE}matrix:\n  product_id: [1, 2.5, true]\n");
+        let tests = expand_yaml(&text).unwrap();
+        let ids: Vec<&str> = tests
+            .iter()
+            .map(|t| t.purchase.product_id.as_str())
+            .collect();
+        assert_eq!(ids, ["1", "2.5", "true"]);
+    }
+}
@@ -1,28 +1,48 @@
-use crate::{project_info::ProjectInfo, test_case::TestCase};
+use crate::{
+    error::CheckError,
+    project_info::ProjectInfo,
+    test_case::{PurchaseData, TestCase},
+};
 use eyre::WrapErr;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 use sha1::{digest::Digest, Sha1};
 use slog::{debug, Logger};
 
 //////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-// Тело запроса к серверу
+/// Тело запроса к серверу
 #[derive(Debug, Serialize)]
-struct JsonRequestBody<'a> {
-    project_name: &'a str,
-    payment_info: String,
-    payment_info_signature: String,
+pub struct JsonRequestBody<'a> {
+    pub project_name: &'a str,
+    /// Данные о платеже в виде JSON, закодированного в base64
+    pub payment_info: String,
+    pub payment_info_signature: String,
 }
 
 //////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-#[allow(dead_code)] // TODO: ???
-#[derive(Debug, Deserialize)]
-struct PurchaseStatus {
-    status: String,
-    description: Option<String>,
-    payload: Option<Vec<String>>,
+/// Расшифрованный результат проверки покупки от сервера
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct PurchaseStatus {
+    pub status: String,
+    pub description: Option<String>,
+    pub payload: Option<Vec<String>>,
+}
+
+impl PurchaseStatus {
+    /// Описание для вывода, "-" если его нет
+    pub fn description_text(&self) -> &str {
+        self.description.as_deref().unwrap_or("-")
+    }
+
+    /// Содержимое покупки через запятую для вывода, "-" если его нет
+    pub fn payload_text(&self) -> String {
+        self.payload
+            .as_ref()
+            .map(|payload| payload.join(", "))
+            .unwrap_or_else(|| "-".to_owned())
+    }
 }
 
 // #[allow(dead_code)]
@@ -44,7 +64,8 @@ struct JsonResponse {
 
 //////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////////
 
-fn calc_signature(data: &[u8], key: &[u8]) -> Result<String, eyre::Error> {
+/// Подпись данных секретным ключом проекта, используется и для запросов, и для ответов
+pub fn calc_signature(data: &[u8], key: &[u8]) -> Result<String, eyre::Error> {
     /*use hmac::{Hmac, Mac};
     let mut hmac: Hmac<Sha1> = Hmac::new_from_slice(key).wrap_err("Hmac create error")?;
     hmac.update(data);
@@ -58,17 +79,23 @@ fn calc_signature(data: &[u8], key: &[u8]) -> Result<String, eyre::Error> {
     Ok(format!("{:x}", hash_number))
 }
 
-// Запускаем проверку покупки
-pub async fn check_purchase(
+/// Успешный ответ сервера на запрос проверки покупки
+#[derive(Debug, Clone)]
+pub struct PurchaseResponse {
+    pub http_status: StatusCode,
+    pub purchase_status: PurchaseStatus,
+}
+
+/// Формируем подписанное тело запроса проверки покупки
+pub fn build_request_body<'a>(
     logger: &Logger,
-    http_client: &Client,
-    project: &ProjectInfo,
-    test: &TestCase,
-) -> Result<(), eyre::Error> {
+    project: &'a ProjectInfo,
+    purchase: &PurchaseData,
+) -> Result<JsonRequestBody<'a>, eyre::Error> {
     // Данные о платеже и подпись
     let purchase_base64_string = {
         let purchase_json_string =
-            serde_json::to_string(&test.purchase).wrap_err("Purchase info serialize failed")?;
+            serde_json::to_string(purchase).wrap_err("Purchase info serialize failed")?;
         debug!(logger, "Request data: {purchase_json_string}");
 
         base64::encode(purchase_json_string)
@@ -79,15 +106,27 @@ pub async fn check_purchase(
     )
     .wrap_err("Send signature calculate")?;
 
+    Ok(JsonRequestBody {
+        project_name: &project.name,
+        payment_info: purchase_base64_string,
+        payment_info_signature: purchase_signature,
+    })
+}
+
+/// Отправляем покупку на сервер и получаем расшифрованный результат проверки
+pub async fn request_purchase(
+    logger: &Logger,
+    http_client: &Client,
+    project: &ProjectInfo,
+    purchase: &PurchaseData,
+) -> Result<PurchaseResponse, eyre::Error> {
+    let request_body = build_request_body(logger, project, purchase)?;
+
     // TODO: Запрос должен был быть GET
     // Выполняем запрос
     let response_obj = http_client
         .post(project.api_url.clone())
-        .json(&JsonRequestBody {
-            project_name: &project.name,
-            payment_info: purchase_base64_string,
-            payment_info_signature: purchase_signature,
-        })
+        .json(&request_body)
         .send()
         .await
         .wrap_err("Test request perform error")?;
@@ -100,19 +139,11 @@ pub async fn check_purchase(
     let response_text = if status.is_success() {
         response_text_result.wrap_err("Response body receive failed")?
     } else {
-        let err = match response_text_result.ok() {
-            Some(response_error_text) => {
-                eyre::eyre!(
-                    "Server response with status {} and text: {}",
-                    status,
-                    response_error_text
-                )
-            }
-            None => {
-                eyre::eyre!("Server response with status {}", status)
-            }
-        };
-        return Err(err);
+        return Err(CheckError::HttpStatus {
+            status,
+            text: response_text_result.ok(),
+        }
+        .into());
     };
     debug!(logger, "Received from server: {response_text}");
 
@@ -128,15 +159,16 @@ pub async fn check_purchase(
     .wrap_err("Received signature calculate")?;
 
     // Проверяем подпись
-    eyre::ensure!(
-        calculated_signature == response_data.data.validation_result_signature,
-        "Response signature invalid: calculated {} != received {}",
-        calculated_signature,
-        response_data.data.validation_result_signature
-    );
+    if calculated_signature != response_data.data.validation_result_signature {
+        return Err(CheckError::SignatureMismatch {
+            calculated: calculated_signature,
+            received: response_data.data.validation_result_signature,
+        }
+        .into());
+    }
 
     // Парсим
-    let response_data = {
+    let purchase_status = {
         let response_json_data = base64::decode(response_data.data.validation_result)
             .wrap_err("Response base64 decode failed")?;
 
@@ -148,12 +180,28 @@ pub async fn check_purchase(
             .wrap_err("Response json parsing failed")?
     };
 
-    eyre::ensure!(
-        response_data.status == test.response.status,
-        "Status invalid: received {} != required {}",
-        response_data.status,
-        test.response.status
-    );
+    Ok(PurchaseResponse {
+        http_status: status,
+        purchase_status,
+    })
+}
 
-    Ok(())
+// Запускаем проверку покупки
+pub async fn check_purchase(
+    logger: &Logger,
+    http_client: &Client,
+    project: &ProjectInfo,
+    test: &TestCase,
+) -> Result<PurchaseResponse, eyre::Error> {
+    let response = request_purchase(logger, http_client, project, &test.purchase).await?;
+
+    if response.purchase_status.status != test.response.status {
+        return Err(CheckError::StatusMismatch {
+            response,
+            required: test.response.status.clone(),
+        }
+        .into());
+    }
+
+    Ok(response)
 }